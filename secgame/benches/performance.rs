@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ndarray::Array1;
+use secgame::{Asset, NetworkGraph, QNetwork, SimulationConfig, Simulator};
+
+/// Build a chain network of `n` nodes, shaped like
+/// [`secgame::create_example_network`] but scalable, for benchmarking
+/// across network sizes.
+fn build_chain_network(n: usize) -> NetworkGraph {
+    let mut network = NetworkGraph::new();
+    let mut previous = None;
+    for i in 0..n {
+        let node = network.add_node(Asset::new(format!("n{i}"), format!("Node {i}"), 1_000.0 * (i as f64 + 1.0), 0.5));
+        if let Some(prev) = previous {
+            network.add_edge(prev, node, 1.0);
+        }
+        previous = Some(node);
+    }
+    network
+}
+
+const HIDDEN_SIZE: usize = 32;
+const NETWORK_SIZES: [usize; 3] = [3, 10, 30];
+
+fn bench_qnetwork_forward(c: &mut Criterion) {
+    let mut group = c.benchmark_group("QNetwork::forward");
+    for &nodes in &NETWORK_SIZES {
+        let state_size = nodes * 3;
+        let network = QNetwork::new(state_size, HIDDEN_SIZE, nodes, 0.001);
+        let state = Array1::from_elem(state_size, 0.5);
+        group.bench_with_input(BenchmarkId::from_parameter(nodes), &nodes, |b, _| {
+            b.iter(|| black_box(network.forward(&state)));
+        });
+    }
+    group.finish();
+}
+
+fn bench_qnetwork_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("QNetwork::update");
+    for &nodes in &NETWORK_SIZES {
+        let state_size = nodes * 3;
+        let state = Array1::from_elem(state_size, 0.5);
+        let target = Array1::from_elem(nodes, 1.0);
+        group.bench_with_input(BenchmarkId::from_parameter(nodes), &nodes, |b, _| {
+            let mut network = QNetwork::new(state_size, HIDDEN_SIZE, nodes, 0.001);
+            b.iter(|| network.update(&state, &target));
+        });
+    }
+    group.finish();
+}
+
+fn bench_simulator_episode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Simulator::run_episode");
+    for &nodes in &NETWORK_SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(nodes), &nodes, |b, &nodes| {
+            let network = build_chain_network(nodes);
+            let mut simulator = Simulator::new(SimulationConfig::default(), network);
+            b.iter(|| simulator.run_episode());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_qnetwork_forward, bench_qnetwork_update, bench_simulator_episode);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+use secgame::{create_example_network, Analyzer, SimulationConfig, Simulator};
+
+fn main() {
+    let network = create_example_network();
+    let config = SimulationConfig { episodes: 200, ..SimulationConfig::default() };
+    let mut simulator = Simulator::new(config, network);
+    let metrics = simulator.run();
+
+    let analyzer = Analyzer::new(&metrics);
+    let report = analyzer.generate_report();
+
+    println!("Success rate: {:.2}%", report.success_rate * 100.0);
+    println!("Detection rate: {:.2}%", report.detection_rate * 100.0);
+    println!("Expected loss: ${:.2}", report.expected_loss);
+    if let Some(phase) = report.weakest_detection_phase {
+        println!("Weakest-detection phase: {:?}", phase);
+    }
+
+    let defense_cost = 20_000.0;
+    let roi = analyzer.roi_analysis(defense_cost);
+    println!("ROI on ${:.0} defense spend: {:.1}%", defense_cost, roi.roi_percent);
+}
@@ -0,0 +1,640 @@
+use std::collections::VecDeque;
+
+use ndarray::{Array1, Array2};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A minimal two-layer feedforward network used as the Q-function
+/// approximator.
+#[derive(Debug, Clone)]
+pub struct QNetwork {
+    w1: Array2<f64>,
+    b1: Array1<f64>,
+    w2: Array2<f64>,
+    b2: Array1<f64>,
+    learning_rate: f64,
+    loss: Loss,
+}
+
+fn relu(x: &Array1<f64>) -> Array1<f64> {
+    x.mapv(|v| v.max(0.0))
+}
+
+/// Loss function controlling the output-layer gradient used by
+/// [`QNetwork::update`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loss {
+    /// Standard squared-error gradient (`output - target`), unbounded and
+    /// sensitive to outlier targets.
+    Mse,
+    /// Behaves like [`Loss::Mse`] near zero but clamps the gradient
+    /// magnitude at `delta`, so large reward spikes don't destabilize
+    /// learning.
+    Huber { delta: f64 },
+}
+
+impl Loss {
+    fn gradient(self, output_error: &Array1<f64>) -> Array1<f64> {
+        match self {
+            Loss::Mse => output_error.clone(),
+            Loss::Huber { delta } => output_error.mapv(|e| e.clamp(-delta, delta)),
+        }
+    }
+}
+
+impl QNetwork {
+    pub fn new(input_size: usize, hidden_size: usize, output_size: usize, learning_rate: f64) -> Self {
+        Self::with_rng(input_size, hidden_size, output_size, learning_rate, &mut rand::thread_rng())
+    }
+
+    /// Like [`QNetwork::new`], but draws its initial weights from a
+    /// `seed`-derived RNG instead of [`rand::thread_rng`], so the same seed
+    /// always produces the same network. See [`DQNAgent::new_seeded`].
+    pub fn new_seeded(input_size: usize, hidden_size: usize, output_size: usize, learning_rate: f64, seed: u64) -> Self {
+        Self::with_rng(input_size, hidden_size, output_size, learning_rate, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(input_size: usize, hidden_size: usize, output_size: usize, learning_rate: f64, rng: &mut impl Rng) -> Self {
+        let scale = 0.1;
+        QNetwork {
+            w1: Array2::from_shape_fn((hidden_size, input_size), |_| rng.gen_range(-scale..scale)),
+            b1: Array1::zeros(hidden_size),
+            w2: Array2::from_shape_fn((output_size, hidden_size), |_| rng.gen_range(-scale..scale)),
+            b2: Array1::zeros(output_size),
+            learning_rate,
+            loss: Loss::Mse,
+        }
+    }
+
+    pub fn forward(&self, state: &Array1<f64>) -> Array1<f64> {
+        let hidden = relu(&(self.w1.dot(state) + &self.b1));
+        self.w2.dot(&hidden) + &self.b2
+    }
+
+    /// The input (state) dimension this network expects.
+    pub fn input_size(&self) -> usize {
+        self.w1.ncols()
+    }
+
+    /// The output (action) dimension this network produces.
+    pub fn output_size(&self) -> usize {
+        self.w2.nrows()
+    }
+
+    /// The loss function used by [`QNetwork::update`].
+    pub fn set_loss(&mut self, loss: Loss) {
+        self.loss = loss;
+    }
+
+    /// The gradient step size used by [`QNetwork::update`].
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.learning_rate = learning_rate;
+    }
+
+    /// Blend `other`'s weights into this network: `self = tau*other +
+    /// (1-tau)*self`. Used for Polyak-averaged target network updates.
+    pub fn blend_from(&mut self, other: &QNetwork, tau: f64) {
+        self.w1 = &self.w1 * (1.0 - tau) + &other.w1 * tau;
+        self.b1 = &self.b1 * (1.0 - tau) + &other.b1 * tau;
+        self.w2 = &self.w2 * (1.0 - tau) + &other.w2 * tau;
+        self.b2 = &self.b2 * (1.0 - tau) + &other.b2 * tau;
+    }
+
+    /// One step of gradient descent toward `target`, using this network's
+    /// [`Loss`].
+    pub fn update(&mut self, state: &Array1<f64>, target: &Array1<f64>) {
+        let hidden_pre = self.w1.dot(state) + &self.b1;
+        let hidden = relu(&hidden_pre);
+        let output = self.w2.dot(&hidden) + &self.b2;
+
+        let output_error = self.loss.gradient(&(&output - target));
+
+        let grad_w2 = output_error
+            .clone()
+            .insert_axis(ndarray::Axis(1))
+            .dot(&hidden.clone().insert_axis(ndarray::Axis(0)));
+        let grad_b2 = output_error.clone();
+
+        let hidden_error = self.w2.t().dot(&output_error) * hidden_pre.mapv(|v| if v > 0.0 { 1.0 } else { 0.0 });
+        let grad_w1 = hidden_error
+            .clone()
+            .insert_axis(ndarray::Axis(1))
+            .dot(&state.clone().insert_axis(ndarray::Axis(0)));
+        let grad_b1 = hidden_error;
+
+        self.w2 = &self.w2 - &(grad_w2 * self.learning_rate);
+        self.b2 = &self.b2 - &(grad_b2 * self.learning_rate);
+        self.w1 = &self.w1 - &(grad_w1 * self.learning_rate);
+        self.b1 = &self.b1 - &(grad_b1 * self.learning_rate);
+    }
+}
+
+/// How the target network is kept in sync with the online network.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetUpdate {
+    /// Hard-copy the online network into the target network every
+    /// `frequency` training steps.
+    Hard { frequency: usize },
+    /// Blend `tau` of the online network into the target network every
+    /// training step, avoiding the abrupt shifts a hard copy causes.
+    Soft { tau: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Experience {
+    pub state: Array1<f64>,
+    pub action: usize,
+    pub reward: f64,
+    pub next_state: Array1<f64>,
+    pub done: bool,
+}
+
+/// A DQN agent with a target network and an experience replay buffer.
+pub struct DQNAgent {
+    q_network: QNetwork,
+    target_network: QNetwork,
+    replay_buffer: VecDeque<Experience>,
+    buffer_capacity: usize,
+    pub epsilon: f64,
+    pub epsilon_min: f64,
+    pub epsilon_decay: f64,
+    pub gamma: f64,
+    pub batch_size: usize,
+    action_size: usize,
+    steps: usize,
+    target_update: TargetUpdate,
+    min_replay_size: usize,
+    /// Whether [`DQNAgent::train`] is allowed to update the network; see
+    /// [`DQNAgent::set_training`].
+    training: bool,
+    /// Drives exploration ([`DQNAgent::select_action`]) and replay sampling
+    /// ([`DQNAgent::train`]). Entropy-seeded by [`DQNAgent::new`]; pin it
+    /// with [`DQNAgent::new_seeded`] for reproducible runs.
+    rng: StdRng,
+    /// Count-based (UCB-style) exploration bonus coefficient `c` added to
+    /// Q-values in [`DQNAgent::best_action`], on top of epsilon-greedy.
+    /// `None` (the default) disables it; see [`DQNAgent::set_exploration_bonus`].
+    exploration_bonus: Option<f64>,
+    /// How many times [`DQNAgent::select_action`] has picked each action,
+    /// indexed by action. Feeds [`DQNAgent::exploration_bonus_for`]'s
+    /// `count[action]` term.
+    action_counts: Vec<usize>,
+    /// Total [`DQNAgent::select_action`] calls so far, the `total` in
+    /// [`DQNAgent::exploration_bonus_for`]'s `ln(total)` term.
+    total_selections: usize,
+}
+
+impl DQNAgent {
+    pub fn new(state_size: usize, action_size: usize, learning_rate: f64) -> Self {
+        let hidden_size = 32;
+        DQNAgent {
+            q_network: QNetwork::new(state_size, hidden_size, action_size, learning_rate),
+            target_network: QNetwork::new(state_size, hidden_size, action_size, learning_rate),
+            replay_buffer: VecDeque::new(),
+            buffer_capacity: 10_000,
+            epsilon: 1.0,
+            epsilon_min: 0.05,
+            epsilon_decay: 0.995,
+            gamma: 0.99,
+            batch_size: 32,
+            action_size,
+            steps: 0,
+            target_update: TargetUpdate::Hard { frequency: 100 },
+            min_replay_size: 500,
+            training: true,
+            rng: StdRng::from_entropy(),
+            exploration_bonus: None,
+            action_counts: vec![0; action_size],
+            total_selections: 0,
+        }
+    }
+
+    /// Like [`DQNAgent::new`], but both the initial network weights and
+    /// every later exploration/replay draw come from a `seed`-derived RNG
+    /// instead of [`rand::thread_rng`], so two agents built with the same
+    /// seed and driven through the same calls make identical decisions.
+    /// Used by regression suites (e.g. golden-metrics benchmarks) that need
+    /// a fully reproducible attacker.
+    pub fn new_seeded(state_size: usize, action_size: usize, learning_rate: f64, seed: u64) -> Self {
+        let hidden_size = 32;
+        DQNAgent {
+            q_network: QNetwork::new_seeded(state_size, hidden_size, action_size, learning_rate, seed),
+            target_network: QNetwork::new_seeded(state_size, hidden_size, action_size, learning_rate, seed.wrapping_add(1)),
+            replay_buffer: VecDeque::new(),
+            buffer_capacity: 10_000,
+            epsilon: 1.0,
+            epsilon_min: 0.05,
+            epsilon_decay: 0.995,
+            gamma: 0.99,
+            batch_size: 32,
+            action_size,
+            steps: 0,
+            target_update: TargetUpdate::Hard { frequency: 100 },
+            min_replay_size: 500,
+            training: true,
+            rng: StdRng::seed_from_u64(seed.wrapping_add(2)),
+            exploration_bonus: None,
+            action_counts: vec![0; action_size],
+            total_selections: 0,
+        }
+    }
+
+    /// How the target network is synced with the online network going
+    /// forward (defaults to a hard copy every 100 training steps).
+    pub fn set_target_update(&mut self, target_update: TargetUpdate) {
+        self.target_update = target_update;
+    }
+
+    /// How many experiences the replay buffer must hold before
+    /// [`DQNAgent::train`] will perform a gradient update (defaults to
+    /// 500). The agent still acts and collects experience below this
+    /// threshold, it just doesn't train on it yet.
+    pub fn set_warmup(&mut self, min_replay_size: usize) {
+        self.min_replay_size = min_replay_size;
+    }
+
+    /// The discount factor applied to future reward in [`DQNAgent::train`]'s
+    /// Q-learning target (defaults to `0.99`).
+    pub fn set_gamma(&mut self, gamma: f64) {
+        self.gamma = gamma;
+    }
+
+    /// Update both the online and target network's learning rate, so it
+    /// can be scheduled (e.g. decayed) from the simulation loop instead of
+    /// being fixed at whatever [`DQNAgent::new`] was built with.
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.q_network.set_learning_rate(learning_rate);
+        self.target_network.set_learning_rate(learning_rate);
+    }
+
+    /// Freeze (`false`) or resume (`true`, the default) learning. While
+    /// frozen, [`DQNAgent::train`] (and so [`DQNAgent::observe_and_learn`])
+    /// is a no-op, and [`DQNAgent::select_action`] acts with `epsilon_min`
+    /// instead of the current, possibly still-decaying `epsilon`.
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+
+    /// Enable a count-based (UCB-style) exploration bonus of
+    /// `c·sqrt(ln(total)/count[action])` added to each action's Q-value in
+    /// [`DQNAgent::best_action`], on top of epsilon-greedy, so
+    /// rarely-visited actions look more attractive than their raw Q-value
+    /// alone would suggest. An action with zero visits gets an infinite
+    /// bonus, guaranteeing every action is tried at least once before the
+    /// bonus prefers one visited action over another. Disabled by default;
+    /// pass `c <= 0.0` to disable it again.
+    pub fn set_exploration_bonus(&mut self, c: f64) {
+        self.exploration_bonus = if c > 0.0 { Some(c) } else { None };
+    }
+
+    /// The state (input) dimension this agent was built for.
+    pub fn state_size(&self) -> usize {
+        self.q_network.input_size()
+    }
+
+    /// The number of distinct actions this agent chooses between.
+    pub fn action_size(&self) -> usize {
+        self.action_size
+    }
+
+    pub fn select_action(&mut self, state: &Array1<f64>) -> usize {
+        let epsilon = if self.training { self.epsilon } else { self.epsilon_min };
+        let action = if self.rng.gen::<f64>() < epsilon { self.rng.gen_range(0..self.action_size) } else { self.best_action(state) };
+        self.action_counts[action] += 1;
+        self.total_selections += 1;
+        action
+    }
+
+    /// How much [`DQNAgent::select_action_masked`] discourages re-picking
+    /// an action already in `failed_actions`: enough to dominate any
+    /// realistic Q-value spread without ruling the action out entirely.
+    const FAILED_ACTION_PENALTY: f64 = 1e6;
+
+    /// Like [`DQNAgent::select_action`], but discourages re-selecting any
+    /// action index already in `failed_actions` (e.g. targets that already
+    /// failed earlier in the same simulated episode) instead of ruling it
+    /// out entirely. Exploration draws favor not-yet-failed actions when
+    /// any remain; the greedy path applies [`DQNAgent::FAILED_ACTION_PENALTY`]
+    /// to a failed action's Q-value before taking the argmax, so a clearly
+    /// superior action can still win even if it previously failed.
+    pub fn select_action_masked(&mut self, state: &Array1<f64>, failed_actions: &[usize]) -> usize {
+        let epsilon = if self.training { self.epsilon } else { self.epsilon_min };
+        if self.rng.gen::<f64>() < epsilon {
+            let candidates: Vec<usize> = (0..self.action_size).filter(|a| !failed_actions.contains(a)).collect();
+            if candidates.is_empty() {
+                self.rng.gen_range(0..self.action_size)
+            } else {
+                candidates[self.rng.gen_range(0..candidates.len())]
+            }
+        } else {
+            self.best_action_masked(state, failed_actions)
+        }
+    }
+
+    fn best_action(&self, state: &Array1<f64>) -> usize {
+        let q_values = self.q_network.forward(state);
+        q_values
+            .iter()
+            .enumerate()
+            .map(|(i, &q)| (i, q + self.exploration_bonus_for(i)))
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// The `c·sqrt(ln(total)/count[action])` term [`DQNAgent::best_action`]
+    /// adds to `action`'s Q-value, or `0.0` if
+    /// [`DQNAgent::set_exploration_bonus`] hasn't been called. An
+    /// never-yet-selected action gets `f64::INFINITY`, so it's always
+    /// preferred over any already-visited action regardless of Q-value.
+    fn exploration_bonus_for(&self, action: usize) -> f64 {
+        let c = match self.exploration_bonus {
+            Some(c) => c,
+            None => return 0.0,
+        };
+        if self.action_counts[action] == 0 {
+            return f64::INFINITY;
+        }
+        c * ((self.total_selections.max(1) as f64).ln() / self.action_counts[action] as f64).sqrt()
+    }
+
+    fn best_action_masked(&self, state: &Array1<f64>, failed_actions: &[usize]) -> usize {
+        let q_values = self.q_network.forward(state);
+        q_values
+            .iter()
+            .enumerate()
+            .map(|(i, &q)| (i, if failed_actions.contains(&i) { q - Self::FAILED_ACTION_PENALTY } else { q }))
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    pub fn store_experience(&mut self, state: Array1<f64>, action: usize, reward: f64, next_state: Array1<f64>, done: bool) {
+        if self.replay_buffer.len() >= self.buffer_capacity {
+            self.replay_buffer.pop_front();
+        }
+        self.replay_buffer.push_back(Experience { state, action, reward, next_state, done });
+    }
+
+    /// Store `(state, action, reward, next_state, done)`, then train and
+    /// decay epsilon on it in one call, for feeding single live-environment
+    /// transitions in outside a [`crate::simulation::Simulator`] episode
+    /// loop. A no-op on the network while [`DQNAgent::set_training`] has
+    /// frozen learning (the experience is still stored).
+    pub fn observe_and_learn(&mut self, state: Array1<f64>, action: usize, reward: f64, next_state: Array1<f64>, done: bool) {
+        self.store_experience(state, action, reward, next_state, done);
+        self.train();
+    }
+
+    pub fn train(&mut self) {
+        if !self.training {
+            return;
+        }
+        if self.replay_buffer.len() < self.batch_size || self.replay_buffer.len() < self.min_replay_size {
+            return;
+        }
+
+        for _ in 0..self.batch_size {
+            let idx = self.rng.gen_range(0..self.replay_buffer.len());
+            let exp = self.replay_buffer[idx].clone();
+
+            let mut target = self.q_network.forward(&exp.state);
+            let next_q = self.target_network.forward(&exp.next_state);
+            let max_next_q = next_q.iter().cloned().fold(f64::MIN, f64::max);
+            target[exp.action] = if exp.done { exp.reward } else { exp.reward + self.gamma * max_next_q };
+
+            self.q_network.update(&exp.state, &target);
+        }
+
+        self.steps += 1;
+        match self.target_update {
+            TargetUpdate::Hard { frequency } => {
+                if self.steps.is_multiple_of(frequency) {
+                    self.target_network = self.q_network.clone();
+                }
+            }
+            TargetUpdate::Soft { tau } => self.target_network.blend_from(&self.q_network, tau),
+        }
+
+        self.epsilon = (self.epsilon * self.epsilon_decay).max(self.epsilon_min);
+    }
+
+    /// The raw Q-network output for `state`, one value per action, before
+    /// [`DQNAgent::get_policy`]'s softmax. Useful for inspecting what the
+    /// agent actually learned rather than just its resulting policy.
+    pub fn q_values(&self, state: &Array1<f64>) -> Array1<f64> {
+        self.q_network.forward(state)
+    }
+
+    /// Softmax policy over Q-values for `state`.
+    pub fn get_policy(&self, state: &Array1<f64>) -> Array1<f64> {
+        self.get_policy_with_temperature(state, 1.0)
+    }
+
+    /// Softmax policy over Q-values for `state`, scaled by `temperature`:
+    /// as `temperature` approaches `0` the distribution concentrates onto
+    /// the argmax action, and as it grows large the distribution approaches
+    /// uniform.
+    pub fn get_policy_with_temperature(&self, state: &Array1<f64>, temperature: f64) -> Array1<f64> {
+        let q_values = self.q_network.forward(state);
+        let scaled = q_values.mapv(|q| q / temperature);
+        let max_scaled = scaled.iter().cloned().fold(f64::MIN, f64::max);
+        let exp: Array1<f64> = scaled.mapv(|q| (q - max_scaled).exp());
+        let sum: f64 = exp.sum();
+        exp / sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huber_gradient_clamps_while_mse_grows_linearly() {
+        let large_error = Array1::from_vec(vec![1_000.0, -1_000.0]);
+
+        let mse = Loss::Mse.gradient(&large_error);
+        assert_eq!(mse, large_error);
+
+        let delta = 1.0;
+        let huber = Loss::Huber { delta }.gradient(&large_error);
+        assert_eq!(huber[0], delta);
+        assert_eq!(huber[1], -delta);
+    }
+
+    #[test]
+    fn train_performs_no_weight_change_until_warmup_threshold_is_reached() {
+        let mut agent = DQNAgent::new(2, 2, 0.1);
+        agent.batch_size = 2;
+        agent.set_warmup(3);
+
+        let before = agent.q_network.clone();
+        for i in 0..2 {
+            agent.store_experience(Array1::from_vec(vec![1.0, 0.0]), i % 2, 1.0, Array1::from_vec(vec![0.0, 1.0]), false);
+        }
+        agent.train();
+        assert_eq!(agent.q_network.w1, before.w1);
+
+        agent.store_experience(Array1::from_vec(vec![1.0, 0.0]), 0, 1.0, Array1::from_vec(vec![0.0, 1.0]), false);
+        agent.train();
+        assert_ne!(agent.q_network.w1, before.w1);
+    }
+
+    #[test]
+    fn set_learning_rate_changes_the_magnitude_of_the_next_weight_update() {
+        let mut agent = DQNAgent::new(2, 2, 0.01);
+        agent.batch_size = 1;
+        agent.set_warmup(0);
+
+        // Zero both networks so the resulting update delta is driven
+        // purely by the learning rate, not by the random initialization.
+        let hidden_size = 32;
+        agent.q_network.w1 = Array2::zeros((hidden_size, 2));
+        agent.q_network.b1 = Array1::zeros(hidden_size);
+        agent.q_network.w2 = Array2::zeros((2, hidden_size));
+        agent.q_network.b2 = Array1::zeros(2);
+        agent.target_network = agent.q_network.clone();
+        let zeroed = agent.q_network.clone();
+
+        let state = Array1::from_vec(vec![1.0, 0.0]);
+        agent.observe_and_learn(state.clone(), 0, 1.0, Array1::from_vec(vec![0.0, 1.0]), false);
+        let small_delta: f64 = (&agent.q_network.b2 - &zeroed.b2).mapv(f64::abs).sum();
+
+        agent.q_network = zeroed.clone();
+        agent.target_network = zeroed.clone();
+        agent.set_learning_rate(1.0);
+        agent.observe_and_learn(state, 0, 1.0, Array1::from_vec(vec![0.0, 1.0]), false);
+        let large_delta: f64 = (&agent.q_network.b2 - &zeroed.b2).mapv(f64::abs).sum();
+
+        assert!(large_delta > small_delta);
+    }
+
+    #[test]
+    fn soft_update_blends_target_strictly_between_old_target_and_online() {
+        let online = QNetwork::new(2, 4, 3, 0.001);
+        let mut target = QNetwork::new(2, 4, 3, 0.001);
+        let old_target_w1 = target.w1.clone();
+
+        target.blend_from(&online, 0.5);
+
+        for ((blended, old), new) in target.w1.iter().zip(old_target_w1.iter()).zip(online.w1.iter()) {
+            let (lo, hi) = if old < new { (old, new) } else { (new, old) };
+            assert!(blended > lo && blended < hi, "{blended} not strictly between {lo} and {hi}");
+        }
+    }
+
+    #[test]
+    fn frozen_training_produces_greedy_actions_and_does_not_mutate_weights() {
+        let mut agent = DQNAgent::new(2, 2, 0.1);
+        agent.batch_size = 1;
+        agent.set_warmup(0);
+        agent.epsilon = 1.0;
+        agent.epsilon_min = 0.0;
+        agent.set_training(false);
+
+        let state = Array1::from_vec(vec![1.0, 0.0]);
+        let expected_action = agent.best_action(&state);
+        for _ in 0..20 {
+            assert_eq!(agent.select_action(&state), expected_action);
+        }
+
+        let before = agent.q_network.clone();
+        agent.observe_and_learn(state.clone(), expected_action, 1.0, Array1::from_vec(vec![0.0, 1.0]), false);
+        assert_eq!(agent.q_network.w1, before.w1);
+        assert_eq!(agent.q_network.w2, before.w2);
+    }
+
+    #[test]
+    fn masked_action_is_not_immediately_reselected() {
+        let mut agent = DQNAgent::new(2, 3, 0.01);
+        agent.epsilon_min = 0.0;
+        agent.set_training(false);
+
+        // Zero out both layers so every action ties on Q-value, isolating
+        // the mask's effect from the network's random initialization.
+        let hidden_size = 32;
+        agent.q_network.w1 = Array2::zeros((hidden_size, 2));
+        agent.q_network.b1 = Array1::zeros(hidden_size);
+        agent.q_network.w2 = Array2::zeros((3, hidden_size));
+        agent.q_network.b2 = Array1::zeros(3);
+
+        let state = Array1::zeros(2);
+        let first = agent.select_action_masked(&state, &[]);
+        let second = agent.select_action_masked(&state, &[first]);
+
+        assert_ne!(second, first);
+    }
+
+    #[test]
+    fn exploration_bonus_picks_an_unvisited_action_before_plain_greedy_would() {
+        let hidden_size = 32;
+        let state = Array1::zeros(2);
+
+        // Zero out the hidden layer and fix known, strictly-ordered
+        // Q-values (action 0 highest, action 2 lowest) so the bonus's
+        // effect is isolated from the network's random init. Actions 0
+        // and 1 have each been visited once; action 2 never has.
+        let fixed = |mut agent: DQNAgent| -> DQNAgent {
+            agent.q_network.w1 = Array2::zeros((hidden_size, 2));
+            agent.q_network.b1 = Array1::zeros(hidden_size);
+            agent.q_network.w2 = Array2::zeros((3, hidden_size));
+            agent.q_network.b2 = Array1::from_vec(vec![2.0, 1.0, 0.0]);
+            agent.epsilon = 0.0;
+            agent.epsilon_min = 0.0;
+            agent.set_training(false);
+            agent.action_counts = vec![1, 1, 0];
+            agent
+        };
+
+        let mut greedy = fixed(DQNAgent::new(2, 3, 0.01));
+        // Plain greedy always prefers action 0's strictly higher Q-value,
+        // never favoring the lower-valued, unvisited action 2.
+        assert_eq!(greedy.select_action(&state), 0);
+
+        let mut bonused = fixed(DQNAgent::new(2, 3, 0.01));
+        bonused.set_exploration_bonus(1.0);
+        // With the bonus enabled, the never-yet-visited action 2 gets an
+        // infinite bonus and wins despite its lower Q-value.
+        assert_eq!(bonused.select_action(&state), 2);
+    }
+
+    #[test]
+    fn q_values_has_one_entry_per_action_and_get_policy_is_their_softmax() {
+        let agent = DQNAgent::new(2, 4, 0.001);
+        let state = Array1::from_vec(vec![0.3, 0.7]);
+
+        let q_values = agent.q_values(&state);
+        assert_eq!(q_values.len(), 4);
+
+        let max_q = q_values.iter().cloned().fold(f64::MIN, f64::max);
+        let exp: Array1<f64> = q_values.mapv(|q| (q - max_q).exp());
+        let expected_policy = &exp / exp.sum();
+
+        let policy = agent.get_policy(&state);
+        for (actual, expected) in policy.iter().zip(expected_policy.iter()) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn low_temperature_concentrates_on_argmax_high_temperature_is_near_uniform() {
+        let mut agent = DQNAgent::new(2, 3, 0.001);
+        // Zero out the hidden layer so Q-values are just a known, widely
+        // separated bias vector, regardless of the network's random init.
+        let hidden_size = 32;
+        agent.q_network.w1 = Array2::zeros((hidden_size, 2));
+        agent.q_network.b1 = Array1::zeros(hidden_size);
+        agent.q_network.w2 = Array2::zeros((3, hidden_size));
+        agent.q_network.b2 = Array1::from_vec(vec![0.0, 10.0, 0.0]);
+        let state = Array1::from_vec(vec![1.0, 0.5]);
+
+        let sharp = agent.get_policy_with_temperature(&state, 0.01);
+        assert!(sharp[1] > 0.99);
+
+        let flat = agent.get_policy_with_temperature(&state, 100.0);
+        let uniform = 1.0 / 3.0;
+        for p in flat.iter() {
+            assert!((p - uniform).abs() < 0.05);
+        }
+    }
+}
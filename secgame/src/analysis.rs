@@ -0,0 +1,824 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+use petgraph::graph::NodeIndex;
+
+use crate::defense::DefenseConfiguration;
+use crate::network::NetworkGraph;
+use crate::path::AttackPath;
+use crate::simulation::{AttackStepTrace, SimulationMetrics};
+use crate::strategy::AttackStrategy;
+use crate::technique::{example_techniques, AccessLevel, AttackPhase};
+
+/// Current on-disk schema version for [`AnalysisReport`]. Bump this and
+/// add a migration arm to [`AnalysisReport::from_json_versioned`]
+/// whenever a field is added, removed, or changes meaning in a way that
+/// breaks deserializing older saved JSON.
+pub const ANALYSIS_REPORT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    /// On-disk schema version this value was produced at or migrated to;
+    /// see [`AnalysisReport::from_json_versioned`].
+    pub schema_version: u32,
+    pub success_rate: f64,
+    pub detection_rate: f64,
+    pub expected_loss: f64,
+    pub high_risk_assets: Vec<String>,
+    /// The observed attack phase with the lowest per-phase detection rate
+    /// (`None` if no phase has been observed yet).
+    pub weakest_detection_phase: Option<AttackPhase>,
+}
+
+/// Errors from [`AnalysisReport::from_json_versioned`].
+#[derive(Debug)]
+pub enum ReportSchemaError {
+    /// The JSON didn't parse, or parsed but didn't match the shape for its
+    /// `schema_version`.
+    Parse(serde_json::Error),
+    /// A `schema_version` newer than this build supports, or one for which
+    /// no migration to [`ANALYSIS_REPORT_SCHEMA_VERSION`] exists.
+    UnsupportedVersion { found: u32 },
+}
+
+impl fmt::Display for ReportSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportSchemaError::Parse(err) => write!(f, "invalid AnalysisReport JSON: {err}"),
+            ReportSchemaError::UnsupportedVersion { found } => write!(
+                f,
+                "unsupported AnalysisReport schema_version {found} (this build supports up to {ANALYSIS_REPORT_SCHEMA_VERSION})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReportSchemaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReportSchemaError::Parse(err) => Some(err),
+            ReportSchemaError::UnsupportedVersion { .. } => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ReportSchemaError {
+    fn from(err: serde_json::Error) -> Self {
+        ReportSchemaError::Parse(err)
+    }
+}
+
+/// Aggregate statistics across several [`AnalysisReport`]s, e.g. one per
+/// simulation seed, so conclusions rest on a distribution instead of a
+/// single run's anecdote. Built by [`AnalysisReport::aggregate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateReport {
+    pub mean_success_rate: f64,
+    pub success_rate_variance: f64,
+    pub mean_detection_rate: f64,
+    pub detection_rate_variance: f64,
+    pub mean_expected_loss: f64,
+    pub expected_loss_variance: f64,
+    /// How many of the aggregated reports flagged each asset id as
+    /// high-risk; ids flagged by zero reports aren't present.
+    pub high_risk_asset_frequencies: HashMap<String, usize>,
+}
+
+impl AnalysisReport {
+    /// Average success rate, detection rate, and expected loss across
+    /// `reports`, with their variance, and the union of their
+    /// `high_risk_assets` each tagged with how many reports flagged it.
+    /// All-zero statistics and an empty frequency map for an empty slice.
+    pub fn aggregate(reports: &[AnalysisReport]) -> AggregateReport {
+        let success_rates: Vec<f64> = reports.iter().map(|r| r.success_rate).collect();
+        let detection_rates: Vec<f64> = reports.iter().map(|r| r.detection_rate).collect();
+        let expected_losses: Vec<f64> = reports.iter().map(|r| r.expected_loss).collect();
+
+        let mut high_risk_asset_frequencies = HashMap::new();
+        for report in reports {
+            for asset_id in &report.high_risk_assets {
+                *high_risk_asset_frequencies.entry(asset_id.clone()).or_insert(0) += 1;
+            }
+        }
+
+        AggregateReport {
+            mean_success_rate: average(&success_rates),
+            success_rate_variance: variance(&success_rates),
+            mean_detection_rate: average(&detection_rates),
+            detection_rate_variance: variance(&detection_rates),
+            mean_expected_loss: average(&expected_losses),
+            expected_loss_variance: variance(&expected_losses),
+            high_risk_asset_frequencies,
+        }
+    }
+
+    /// Deserialize `json`, rejecting any `schema_version` this build
+    /// doesn't recognize with a descriptive [`ReportSchemaError`] instead
+    /// of a raw serde failure. Missing `schema_version` (e.g. JSON saved
+    /// before this field existed) is treated as version `1`, the first
+    /// version that shipped this field.
+    pub fn from_json_versioned(json: &str) -> Result<Self, ReportSchemaError> {
+        let mut raw: serde_json::Value = serde_json::from_str(json)?;
+        let found = match raw.get("schema_version") {
+            Some(version) => version.as_u64().unwrap_or(0) as u32,
+            None => {
+                raw.as_object_mut().expect("AnalysisReport JSON is an object").insert(
+                    "schema_version".to_string(),
+                    serde_json::Value::from(ANALYSIS_REPORT_SCHEMA_VERSION),
+                );
+                ANALYSIS_REPORT_SCHEMA_VERSION
+            }
+        };
+
+        if found != ANALYSIS_REPORT_SCHEMA_VERSION {
+            return Err(ReportSchemaError::UnsupportedVersion { found });
+        }
+        Ok(serde_json::from_value(raw)?)
+    }
+}
+
+/// Return on investment for a hypothetical defense spend, derived from a
+/// simulation's observed expected loss and detection rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoiReport {
+    /// Expected loss avoided by detecting attacks, at the simulated
+    /// detection rate.
+    pub prevention_value: f64,
+    pub defense_cost: f64,
+    /// `prevention_value - defense_cost`.
+    pub net_benefit: f64,
+    /// `net_benefit / defense_cost * 100`, or `0.0` if `defense_cost` is
+    /// zero.
+    pub roi_percent: f64,
+}
+
+/// One named defense configuration's showing in a [`Analyzer::compare_defenses`]
+/// comparison, renderable as a table via [`tabled`].
+#[derive(Debug, Clone, Serialize, Deserialize, Tabled)]
+pub struct DefenseComparisonRow {
+    /// 1-based position among the compared configs, best (lowest attacker
+    /// value) first.
+    pub rank: usize,
+    pub name: String,
+    pub expected_attacker_value: f64,
+}
+
+/// One candidate edge removal in an [`Analyzer::recommend_segmentation`]
+/// recommendation list, renderable as a table via [`tabled`].
+#[derive(Debug, Clone, Serialize, Deserialize, Tabled)]
+pub struct SegmentationRecommendation {
+    /// 1-based position among the recommendations, best (largest expected
+    /// value reduction) first.
+    pub rank: usize,
+    pub from_asset_id: String,
+    pub to_asset_id: String,
+    /// How much removing this edge would lower the optimal attacker's
+    /// expected value against the network's highest-value asset.
+    pub expected_value_reduction: f64,
+}
+
+/// One node's entry in an [`Analyzer::top_defense_gaps`] recommendation
+/// list, renderable as a table via [`tabled`].
+#[derive(Debug, Clone, Serialize, Deserialize, Tabled)]
+pub struct DefenseGapRow {
+    /// 1-based position among the reported gaps, worst (largest gap) first.
+    pub rank: usize,
+    pub asset_id: String,
+    /// `value * vulnerability * (1 - coverage)`; see
+    /// [`DefenseConfiguration::gap_map`].
+    pub gap: f64,
+}
+
+/// A named `(x, y)` series, suitable for feeding `plotters` or a JS chart
+/// library directly. `x` is the 0-based episode index.
+pub type PlotSeries = Vec<(f64, f64)>;
+
+/// [`Analyzer::plot_series`]'s output: one series per metric worth
+/// plotting over the course of a run, every series the same length as the
+/// number of recorded episodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlotData {
+    pub attacker_reward: PlotSeries,
+    pub defender_reward: PlotSeries,
+    /// Moving average of `attacker_reward` over [`Analyzer::plot_series`]'s
+    /// `moving_average_window`.
+    pub attacker_reward_moving_average: PlotSeries,
+    /// Moving average of `defender_reward` over [`Analyzer::plot_series`]'s
+    /// `moving_average_window`.
+    pub defender_reward_moving_average: PlotSeries,
+    /// Cumulative success rate through each episode, i.e. the fraction of
+    /// episodes so far whose attack succeeded.
+    pub success_rate: PlotSeries,
+    /// Cumulative detection rate through each episode, i.e. the fraction of
+    /// episodes so far whose attack was detected.
+    pub detection_rate: PlotSeries,
+}
+
+/// Turns raw [`SimulationMetrics`] into a human-readable report.
+pub struct Analyzer<'a> {
+    metrics: &'a SimulationMetrics,
+}
+
+impl<'a> Analyzer<'a> {
+    pub fn new(metrics: &'a SimulationMetrics) -> Self {
+        Analyzer { metrics }
+    }
+
+    pub fn generate_report(&self) -> AnalysisReport {
+        let avg_attacker_reward = average(&self.metrics.episode_rewards_attacker);
+        AnalysisReport {
+            schema_version: ANALYSIS_REPORT_SCHEMA_VERSION,
+            success_rate: self.metrics.success_rate(),
+            detection_rate: self.metrics.detection_rate(),
+            expected_loss: avg_attacker_reward.max(0.0) * self.metrics.success_rate(),
+            high_risk_assets: Vec::new(),
+            weakest_detection_phase: self.weakest_detection_phase(),
+        }
+    }
+
+    /// The observed phase with the lowest detection rate, breaking ties by
+    /// declaration order in [`AttackPhase`] so the choice is deterministic.
+    fn weakest_detection_phase(&self) -> Option<AttackPhase> {
+        self.metrics
+            .detection_rate_by_phase()
+            .into_iter()
+            .min_by(|(a_phase, a_rate), (b_phase, b_rate)| {
+                a_rate.partial_cmp(b_rate).unwrap_or(std::cmp::Ordering::Equal).then((*a_phase as u8).cmp(&(*b_phase as u8)))
+            })
+            .map(|(phase, _)| phase)
+    }
+
+    /// Attacker and defender reward series, in episode order.
+    pub fn get_reward_trends(&self) -> (Vec<f64>, Vec<f64>) {
+        (self.metrics.episode_rewards_attacker.clone(), self.metrics.episode_rewards_defender.clone())
+    }
+
+    /// Structured complement to [`Analyzer::get_reward_trends`]: every
+    /// series worth plotting over a run, shaped as `(x, y)` points so a
+    /// caller can hand them straight to `plotters` or a JS chart. Every
+    /// series is as long as `self.metrics.total_episodes`; empty if no
+    /// episodes have been recorded. `moving_average_window` below `1` is
+    /// treated as `1` (the series itself, unsmoothed).
+    pub fn plot_series(&self, moving_average_window: usize) -> PlotData {
+        let attacker_reward = &self.metrics.episode_rewards_attacker;
+        let defender_reward = &self.metrics.episode_rewards_defender;
+        PlotData {
+            attacker_reward: to_series(attacker_reward),
+            defender_reward: to_series(defender_reward),
+            attacker_reward_moving_average: moving_average_series(attacker_reward, moving_average_window),
+            defender_reward_moving_average: moving_average_series(defender_reward, moving_average_window),
+            success_rate: cumulative_rate_series(&self.metrics.episode_successes),
+            detection_rate: cumulative_rate_series(&self.metrics.episode_detections),
+        }
+    }
+
+    /// Average attacker reward in this simulation's metrics minus the
+    /// average in `baseline`'s, i.e. how much more reward the learned
+    /// agent captured than [`crate::simulation::Simulator::run_baseline`]'s
+    /// deterministic greedy attacker. Positive means the agent learned
+    /// something; at or below zero means it's doing no better than always
+    /// attacking the highest-value node.
+    pub fn reward_lift_over_baseline(&self, baseline: &SimulationMetrics) -> f64 {
+        average(&self.metrics.episode_rewards_attacker) - average(&baseline.episode_rewards_attacker)
+    }
+
+    /// ROI of spending `defense_cost` on defenses, using this simulation's
+    /// expected loss and detection rate as the basis for the loss that
+    /// spend would prevent.
+    pub fn roi_analysis(&self, defense_cost: f64) -> RoiReport {
+        let report = self.generate_report();
+        let prevention_value = report.expected_loss * report.detection_rate;
+        let net_benefit = prevention_value - defense_cost;
+        let roi_percent = if defense_cost > 0.0 { net_benefit / defense_cost * 100.0 } else { 0.0 };
+        RoiReport { prevention_value, defense_cost, net_benefit, roi_percent }
+    }
+
+    /// Compare candidate defense configurations on `network` by aggregate
+    /// expected attacker value across every node, using each node's
+    /// best-available initial-access technique as [`crate::game::SecurityGame::from_network`]
+    /// does. Ranked best (lowest attacker value) first.
+    pub fn compare_defenses(&self, network: &NetworkGraph, configs: &[(String, DefenseConfiguration)]) -> Vec<DefenseComparisonRow> {
+        let mut rows: Vec<(String, f64)> = configs
+            .iter()
+            .map(|(name, defense)| (name.clone(), Self::aggregate_attacker_value(network, defense)))
+            .collect();
+        rows.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, (name, expected_attacker_value))| DefenseComparisonRow { rank: i + 1, name, expected_attacker_value })
+            .collect()
+    }
+
+    /// Recommend where to spend next: the `top_n` nodes in `network` with
+    /// the largest defense gap under `defense` (see
+    /// [`DefenseConfiguration::gap_map`]), worst first.
+    pub fn top_defense_gaps(network: &NetworkGraph, defense: &DefenseConfiguration, top_n: usize) -> Vec<DefenseGapRow> {
+        let mut gaps: Vec<(NodeIndex, f64)> = defense.gap_map(network).into_iter().collect();
+        gaps.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        gaps.into_iter()
+            .take(top_n)
+            .enumerate()
+            .map(|(i, (node, gap))| DefenseGapRow { rank: i + 1, asset_id: network[node].id.clone(), gap })
+            .collect()
+    }
+
+    /// For each edge in `network`, recompute the optimal attacker's
+    /// expected value against the network's highest-value asset with that
+    /// edge removed, and rank edges by how much removing them reduces it
+    /// (best segmentation candidates, those that most starve the best
+    /// attacker, first). Empty if `network` has no edges or no nodes.
+    pub fn recommend_segmentation(network: &NetworkGraph, defense: &DefenseConfiguration) -> Vec<SegmentationRecommendation> {
+        let Some(target) = network
+            .node_indices()
+            .max_by(|&a, &b| network[a].value.partial_cmp(&network[b].value).unwrap_or(std::cmp::Ordering::Equal))
+        else {
+            return Vec::new();
+        };
+
+        let techniques = example_techniques();
+        let baseline_value = Self::optimal_expected_value(network, &techniques, defense, target);
+
+        let mut rows: Vec<(petgraph::graph::EdgeIndex, f64)> = network
+            .edge_indices()
+            .map(|edge| {
+                let mut pruned = network.clone();
+                pruned.remove_edge(edge);
+                let reduced_value = Self::optimal_expected_value(&pruned, &techniques, defense, target);
+                (edge, baseline_value - reduced_value)
+            })
+            .collect();
+        rows.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, (edge, expected_value_reduction))| {
+                let (from, to) = network.edge_endpoints(edge).expect("edge came from this network's own edge_indices");
+                SegmentationRecommendation {
+                    rank: i + 1,
+                    from_asset_id: network[from].id.clone(),
+                    to_asset_id: network[to].id.clone(),
+                    expected_value_reduction,
+                }
+            })
+            .collect()
+    }
+
+    /// The optimal attacker's expected value against `target`, with
+    /// `defense`'s coverage at each hop folded into that hop's detection
+    /// probability. `0.0` if `target` is unreachable from every entry.
+    fn optimal_expected_value(
+        network: &NetworkGraph,
+        techniques: &[crate::technique::AttackTechnique],
+        defense: &DefenseConfiguration,
+        target: NodeIndex,
+    ) -> f64 {
+        let strategy = AttackStrategy::new(network, techniques);
+        let Some(mut path) = strategy.generate_optimal_path(target) else {
+            return 0.0;
+        };
+        for step in &mut path.steps {
+            step.detection_probability = (step.detection_probability + defense.effectiveness_at(step.node)).min(1.0);
+        }
+        path.calculate_expected_value()
+    }
+
+    /// Expected time to compromise (see
+    /// [`AttackPath::calculate_time_to_compromise`]) each node in `network`
+    /// reachable from `entry`, via the best path
+    /// [`crate::strategy::AttackStrategy::find_path_to_target`] finds to
+    /// it. A standard dwell-time metric for judging whether a critical
+    /// asset's detection controls have enough time to react before an
+    /// attacker who's already inside reaches it.
+    pub fn time_to_compromise_report(network: &NetworkGraph, entry: NodeIndex) -> Vec<(String, f64)> {
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(network, &techniques);
+
+        network
+            .node_indices()
+            .filter(|&n| n != entry)
+            .filter_map(|target| {
+                let path = strategy.find_path_to_target(entry, target)?;
+                Some((network[target].id.clone(), path.calculate_time_to_compromise()))
+            })
+            .collect()
+    }
+
+    /// Names of nodes in `network` that an attacker entering at `entry`
+    /// cannot reach by following edges forward.
+    pub fn unreachable_assets(network: &NetworkGraph, entry: NodeIndex) -> Vec<String> {
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(network, &techniques);
+        let reachable = strategy.reachable_targets(entry);
+
+        network.node_indices().filter(|n| !reachable.contains(n)).map(|n| network[n].id.clone()).collect()
+    }
+
+    /// Render a [`crate::agent::DQNAgent::q_values`] output as a
+    /// human-readable table, mapping each action index back to the asset
+    /// id it targets (per [`crate::simulation::Simulator::encode_state`]'s
+    /// node ordering), one line per node, highest Q-value first.
+    pub fn narrate_q_values(network: &NetworkGraph, q_values: &Array1<f64>) -> String {
+        let mut rows: Vec<(String, f64)> =
+            network.node_indices().zip(q_values.iter()).map(|(node, &q)| (network[node].id.clone(), q)).collect();
+        rows.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        rows.into_iter().map(|(asset_id, q)| format!("{asset_id}: {q:.4}")).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Render a [`crate::simulation::Simulator::record_best_episode`] trace
+    /// as a human-readable narrative, one line per step.
+    pub fn narrate_best_episode(trace: &[AttackStepTrace]) -> String {
+        trace
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                format!(
+                    "{}. attacked `{}` via `{}` (success {:.0}%, detection {:.0}%), reaching {:?} access",
+                    i + 1,
+                    step.asset_id,
+                    step.technique_id,
+                    step.success_probability * 100.0,
+                    step.detection_probability * 100.0,
+                    step.access_level,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Sum of expected attacker value across every node, each attacked with
+    /// its best available initial-access technique.
+    fn aggregate_attacker_value(network: &NetworkGraph, defense: &DefenseConfiguration) -> f64 {
+        let techniques = example_techniques();
+        network
+            .node_indices()
+            .filter_map(|node| {
+                let asset = &network[node];
+                let technique = techniques
+                    .iter()
+                    .filter(|t| t.phase == AttackPhase::InitialAccess && t.required_access <= AccessLevel::None)
+                    .max_by(|x, y| x.success_rate.partial_cmp(&y.success_rate).unwrap_or(std::cmp::Ordering::Equal))?;
+
+                let mut adjusted = technique.clone();
+                adjusted.detectability = (technique.detectability + defense.detection_boost_at(node, technique.phase)).min(1.0);
+
+                let mut path = AttackPath::new(asset.value);
+                path.add_step(node, &adjusted, 1.0, defense.is_honeypot(node), asset.vulnerability, AccessLevel::None).ok()?;
+                Some(path.calculate_expected_value())
+            })
+            .sum()
+    }
+}
+
+/// Criticality-weighted expected loss across `network`: `value * criticality
+/// * success_probability` summed over every node with an entry in
+/// `success_probs` (nodes without one contribute nothing). Unlike
+/// [`Analyzer::generate_report`]'s `expected_loss`, which is a single
+/// simulation-wide average, this weights per-asset so that a high-criticality
+/// asset (e.g. a database with heavy compliance exposure) counts for more
+/// than its dollar `value` alone would suggest.
+pub fn compute_expected_loss(network: &NetworkGraph, success_probs: &HashMap<NodeIndex, f64>) -> f64 {
+    network
+        .node_indices()
+        .map(|node| {
+            let asset = &network[node];
+            let success_probability = success_probs.get(&node).copied().unwrap_or(0.0);
+            asset.value * asset.criticality * success_probability
+        })
+        .sum()
+}
+
+fn average(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn variance(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        let mean = average(values);
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+    }
+}
+
+/// `values` paired with their 0-based index, for [`Analyzer::plot_series`].
+fn to_series(values: &[f64]) -> PlotSeries {
+    values.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect()
+}
+
+/// Moving average of `values` over a trailing `window`, one point per
+/// index (the window shrinks to whatever's available at the start of the
+/// series rather than leaving those points unplotted).
+fn moving_average_series(values: &[f64], window: usize) -> PlotSeries {
+    let window = window.max(1);
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, _)| {
+            let start = i.saturating_sub(window - 1);
+            let trailing = &values[start..=i];
+            (i as f64, trailing.iter().sum::<f64>() / trailing.len() as f64)
+        })
+        .collect()
+}
+
+/// Running fraction of `true` values in `flags` through each index, for
+/// [`Analyzer::plot_series`]'s `success_rate`/`detection_rate`.
+fn cumulative_rate_series(flags: &[bool]) -> PlotSeries {
+    let mut hits = 0usize;
+    flags
+        .iter()
+        .enumerate()
+        .map(|(i, &hit)| {
+            if hit {
+                hits += 1;
+            }
+            (i as f64, hits as f64 / (i + 1) as f64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roi_analysis_matches_expected_arithmetic() {
+        let metrics = SimulationMetrics {
+            episode_rewards_attacker: vec![1_000.0, 1_000.0],
+            episode_rewards_defender: vec![-1_000.0, -1_000.0],
+            attacks_detected: 1,
+            attacks_succeeded: 2,
+            total_episodes: 2,
+            phase_detections: HashMap::new(),
+            stop_reason: None,
+            ..SimulationMetrics::default()
+        };
+        let analyzer = Analyzer::new(&metrics);
+
+        let roi = analyzer.roi_analysis(200.0);
+        let expected_loss = 1_000.0 * 1.0;
+        let prevention_value = expected_loss * 0.5;
+        assert_eq!(roi.prevention_value, prevention_value);
+        assert_eq!(roi.net_benefit, prevention_value - 200.0);
+        assert_eq!(roi.roi_percent, (prevention_value - 200.0) / 200.0 * 100.0);
+    }
+
+    #[test]
+    fn reward_lift_is_positive_when_the_agent_beats_the_baseline() {
+        let agent_metrics = SimulationMetrics { episode_rewards_attacker: vec![100.0, 200.0], ..SimulationMetrics::default() };
+        let baseline_metrics = SimulationMetrics { episode_rewards_attacker: vec![50.0, 50.0], ..SimulationMetrics::default() };
+        let analyzer = Analyzer::new(&agent_metrics);
+
+        assert_eq!(analyzer.reward_lift_over_baseline(&baseline_metrics), 150.0 - 50.0);
+    }
+
+    #[test]
+    fn report_names_the_phase_with_the_lowest_detection_rate() {
+        let mut phase_detections = HashMap::new();
+        phase_detections.insert(crate::technique::AttackPhase::Execution, (8, 10));
+        phase_detections.insert(crate::technique::AttackPhase::InitialAccess, (1, 10));
+        let metrics = SimulationMetrics { phase_detections, total_episodes: 20, ..SimulationMetrics::default() };
+        let analyzer = Analyzer::new(&metrics);
+
+        let report = analyzer.generate_report();
+        assert_eq!(report.weakest_detection_phase, Some(crate::technique::AttackPhase::InitialAccess));
+    }
+
+    #[test]
+    fn aggregate_averages_rates_and_counts_high_risk_asset_frequency() {
+        let make_report = |success_rate: f64, high_risk_assets: Vec<String>| AnalysisReport {
+            schema_version: ANALYSIS_REPORT_SCHEMA_VERSION,
+            success_rate,
+            detection_rate: 0.0,
+            expected_loss: 0.0,
+            high_risk_assets,
+            weakest_detection_phase: None,
+        };
+        let reports = vec![
+            make_report(0.2, vec!["db".to_string()]),
+            make_report(0.4, vec!["db".to_string(), "web".to_string()]),
+            make_report(0.6, vec![]),
+        ];
+
+        let aggregate = AnalysisReport::aggregate(&reports);
+
+        assert_eq!(aggregate.mean_success_rate, (0.2 + 0.4 + 0.6) / 3.0);
+        assert_eq!(aggregate.high_risk_asset_frequencies["db"], 2);
+        assert_eq!(aggregate.high_risk_asset_frequencies["web"], 1);
+    }
+
+    #[test]
+    fn time_to_compromise_report_covers_every_reachable_asset() {
+        use crate::network::create_example_network;
+
+        let network = create_example_network();
+        let web = crate::network::node_by_id(&network, "web").expect("example network has a web node");
+
+        let report = Analyzer::time_to_compromise_report(&network, web);
+        let reported_ids: Vec<&str> = report.iter().map(|(id, _)| id.as_str()).collect();
+        assert!(reported_ids.contains(&"app"));
+        assert!(reported_ids.contains(&"db"));
+        assert!(!reported_ids.contains(&"web"));
+    }
+
+    #[test]
+    fn disconnected_asset_is_reported_unreachable() {
+        use crate::network::{Asset, NetworkGraph};
+
+        let mut network = NetworkGraph::new();
+        let entry = network.add_node(Asset::new("entry", "Entry", 0.0, 0.5));
+        network.add_node(Asset::new("isolated", "Isolated", 0.0, 0.5));
+
+        let unreachable = Analyzer::unreachable_assets(&network, entry);
+        assert_eq!(unreachable, vec!["isolated".to_string()]);
+    }
+
+    #[test]
+    fn greedy_defense_ranks_better_than_uniform() {
+        use crate::defense::DefenseStrategy;
+        use crate::network::create_example_network;
+
+        let network = create_example_network();
+        let budget = 10_000.0;
+        let strategy = DefenseStrategy::new();
+        let configs = vec![
+            ("greedy".to_string(), strategy.greedy_allocate(&network, budget)),
+            ("uniform".to_string(), strategy.uniform_allocate(&network, budget)),
+        ];
+
+        let metrics = SimulationMetrics::default();
+        let analyzer = Analyzer::new(&metrics);
+        let rows = analyzer.compare_defenses(&network, &configs);
+
+        assert_eq!(rows[0].rank, 1);
+        assert_eq!(rows[0].name, "greedy");
+        assert!(rows[0].expected_attacker_value < rows[1].expected_attacker_value);
+    }
+
+    #[test]
+    fn removing_the_top_recommended_edge_lowers_the_optimal_path_value() {
+        use crate::network::create_example_network;
+        use crate::technique::example_techniques;
+
+        let network = create_example_network();
+        let defense = DefenseConfiguration::new();
+
+        let recommendations = Analyzer::recommend_segmentation(&network, &defense);
+        assert!(!recommendations.is_empty());
+        assert_eq!(recommendations[0].rank, 1);
+        assert!(recommendations[0].expected_value_reduction > 0.0);
+
+        let db = crate::network::node_by_id(&network, "db").expect("example network has a db node");
+        let techniques = example_techniques();
+        let before = AttackStrategy::new(&network, &techniques)
+            .generate_optimal_path(db)
+            .map(|p| p.calculate_expected_value())
+            .unwrap_or(0.0);
+
+        let mut pruned = network.clone();
+        let edge = pruned
+            .find_edge(
+                crate::network::node_by_id(&pruned, &recommendations[0].from_asset_id).unwrap(),
+                crate::network::node_by_id(&pruned, &recommendations[0].to_asset_id).unwrap(),
+            )
+            .expect("recommended edge exists in the network");
+        pruned.remove_edge(edge);
+        let after = AttackStrategy::new(&pruned, &techniques)
+            .generate_optimal_path(db)
+            .map(|p| p.calculate_expected_value())
+            .unwrap_or(0.0);
+
+        assert!(after < before);
+    }
+
+    #[test]
+    fn top_defense_gaps_ranks_the_uncovered_high_value_node_first() {
+        use crate::defense::DefenseType;
+        use crate::network::{create_example_network, node_by_id};
+
+        let network = create_example_network();
+        let web = node_by_id(&network, "web").expect("example network has a web node");
+
+        let mut defense = DefenseConfiguration::new();
+        defense.allocate(web, DefenseType::Monitoring);
+        // `db` is both higher-value and left uncovered, so it should
+        // surface as the top recommendation over the partially-covered,
+        // lower-value `web`.
+
+        let gaps = Analyzer::top_defense_gaps(&network, &defense, 2);
+        assert_eq!(gaps[0].rank, 1);
+        assert_eq!(gaps[0].asset_id, "db");
+        assert!(gaps[0].gap > gaps[1].gap);
+    }
+
+    #[test]
+    fn report_round_trips_through_versioned_json() {
+        let metrics = SimulationMetrics::default();
+        let report = Analyzer::new(&metrics).generate_report();
+        let json = serde_json::to_string(&report).expect("AnalysisReport always serializes");
+
+        let restored = AnalysisReport::from_json_versioned(&json).expect("current-version JSON round-trips");
+        assert_eq!(restored.success_rate, report.success_rate);
+    }
+
+    #[test]
+    fn unknown_report_schema_version_is_a_descriptive_error_not_a_raw_serde_failure() {
+        let payload = r#"{"schema_version":7,"success_rate":0.0,"detection_rate":0.0,"expected_loss":0.0,
+            "high_risk_assets":[],"weakest_detection_phase":null}"#;
+
+        let err = match AnalysisReport::from_json_versioned(payload) {
+            Err(err) => err,
+            Ok(_) => panic!("expected an unsupported-version error"),
+        };
+        match err {
+            ReportSchemaError::UnsupportedVersion { found } => assert_eq!(found, 7),
+            ReportSchemaError::Parse(err) => panic!("expected UnsupportedVersion, got a raw parse error: {err}"),
+        }
+        assert!(err.to_string().contains('7'));
+    }
+
+    #[test]
+    fn narrate_best_episode_mentions_the_attacked_asset_and_technique() {
+        use crate::technique::AccessLevel;
+
+        let trace = vec![AttackStepTrace {
+            asset_id: "db".to_string(),
+            technique_id: "sqli".to_string(),
+            success_probability: 0.6,
+            detection_probability: 0.3,
+            access_level: AccessLevel::User,
+        }];
+
+        let narrative = Analyzer::narrate_best_episode(&trace);
+        assert!(narrative.contains("db"));
+        assert!(narrative.contains("sqli"));
+        assert!(narrative.contains("60%"));
+    }
+
+    #[test]
+    fn narrate_q_values_lists_the_highest_valued_asset_first() {
+        let network = crate::network::create_example_network();
+        let db = crate::network::node_by_id(&network, "db").expect("example network has a db node");
+
+        let q_values: Array1<f64> =
+            Array1::from_iter(network.node_indices().map(|n| if n == db { 9.0 } else { 0.0 }));
+
+        let narrative = Analyzer::narrate_q_values(&network, &q_values);
+        let first_line = narrative.lines().next().expect("narrative has at least one line");
+        assert!(first_line.starts_with("db"));
+        assert!(first_line.contains("9.0000"));
+    }
+
+    #[test]
+    fn raising_criticality_increases_expected_loss_proportionally() {
+        use crate::network::{create_example_network, node_by_id};
+
+        let mut network = create_example_network();
+        let db = node_by_id(&network, "db").expect("example network has a db node");
+
+        let mut success_probs = HashMap::new();
+        success_probs.insert(db, 0.4);
+
+        let baseline = compute_expected_loss(&network, &success_probs);
+
+        network[db].criticality = 2.0;
+        let doubled = compute_expected_loss(&network, &success_probs);
+
+        assert_eq!(doubled, baseline * 2.0);
+    }
+
+    #[test]
+    fn plot_series_lengths_match_the_recorded_episode_count() {
+        let metrics = SimulationMetrics {
+            episode_rewards_attacker: vec![10.0, -5.0, 20.0],
+            episode_rewards_defender: vec![-10.0, 5.0, -20.0],
+            episode_successes: vec![true, false, true],
+            episode_detections: vec![false, true, true],
+            total_episodes: 3,
+            ..SimulationMetrics::default()
+        };
+        let analyzer = Analyzer::new(&metrics);
+
+        let plot = analyzer.plot_series(2);
+        assert_eq!(plot.attacker_reward.len(), metrics.total_episodes);
+        assert_eq!(plot.defender_reward.len(), metrics.total_episodes);
+        assert_eq!(plot.attacker_reward_moving_average.len(), metrics.total_episodes);
+        assert_eq!(plot.defender_reward_moving_average.len(), metrics.total_episodes);
+        assert_eq!(plot.success_rate.len(), metrics.total_episodes);
+        assert_eq!(plot.detection_rate.len(), metrics.total_episodes);
+
+        assert_eq!(plot.success_rate, vec![(0.0, 1.0), (1.0, 0.5), (2.0, 2.0 / 3.0)]);
+        assert_eq!(plot.detection_rate, vec![(0.0, 0.0), (1.0, 0.5), (2.0, 2.0 / 3.0)]);
+    }
+}
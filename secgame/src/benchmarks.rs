@@ -0,0 +1,160 @@
+//! Reproducible benchmark scenarios for catching behavioral regressions in
+//! the simulation math.
+//!
+//! Each [`GoldenScenario`] pins a seed, network, and config so
+//! [`run_scenario`] is fully deterministic: [`DQNAgent::new_seeded`] makes
+//! both the initial weights and every exploration/replay draw reproducible,
+//! and [`Simulator::set_seed`] does the same for observation noise. A
+//! scenario's recorded "golden" [`GoldenMetrics`] (stored as JSON in
+//! [`mod tests`]) is what a re-run is checked against; [`compare`] reports
+//! which metric drifted, and by how much, instead of just failing.
+
+use crate::agent::DQNAgent;
+use crate::network::NetworkGraph;
+use crate::simulation::{SimulationConfig, Simulator};
+
+/// A fixed scenario: a network-building function plus the seed and episode
+/// count to run it with. A function pointer (rather than a stored
+/// [`NetworkGraph`]) keeps scenarios cheap to declare as `static` data.
+pub struct GoldenScenario {
+    pub name: &'static str,
+    pub network: fn() -> NetworkGraph,
+    pub seed: u64,
+    pub episodes: usize,
+}
+
+/// Summary metrics recorded (or re-derived) for a [`GoldenScenario`] run.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GoldenMetrics {
+    pub success_rate: f64,
+    pub detection_rate: f64,
+    pub mean_attacker_reward: f64,
+    pub mean_defender_reward: f64,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Run `scenario` to completion and summarize it as [`GoldenMetrics`].
+pub fn run_scenario(scenario: &GoldenScenario) -> GoldenMetrics {
+    let network = (scenario.network)();
+    let node_count = network.node_count();
+    let state_size = node_count * 3;
+    let config = SimulationConfig { episodes: scenario.episodes, ..SimulationConfig::default() };
+
+    let agent = DQNAgent::new_seeded(state_size, node_count, config.learning_rate, scenario.seed);
+    let mut simulator = Simulator::with_pretrained_agent(config, network, agent, None);
+    simulator.set_seed(scenario.seed.wrapping_add(1));
+
+    let metrics = simulator.run();
+    GoldenMetrics {
+        success_rate: metrics.success_rate(),
+        detection_rate: metrics.detection_rate(),
+        mean_attacker_reward: mean(&metrics.episode_rewards_attacker),
+        mean_defender_reward: mean(&metrics.episode_rewards_defender),
+    }
+}
+
+/// Metric names in `actual` that differ from `golden` by more than
+/// `tolerance`, each described as `"<metric>: golden <g>, actual <a>"`.
+/// Empty means `actual` matches `golden` within tolerance.
+pub fn compare(golden: &GoldenMetrics, actual: &GoldenMetrics, tolerance: f64) -> Vec<String> {
+    let mut drifted = Vec::new();
+    let mut check = |name: &str, g: f64, a: f64| {
+        if (g - a).abs() > tolerance {
+            drifted.push(format!("{name}: golden {g}, actual {a}"));
+        }
+    };
+    check("success_rate", golden.success_rate, actual.success_rate);
+    check("detection_rate", golden.detection_rate, actual.detection_rate);
+    check("mean_attacker_reward", golden.mean_attacker_reward, actual.mean_attacker_reward);
+    check("mean_defender_reward", golden.mean_defender_reward, actual.mean_defender_reward);
+    drifted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Asset;
+
+    fn small_network() -> NetworkGraph {
+        crate::network::create_example_network()
+    }
+
+    /// A six-node network spanning two lateral-movement branches off a
+    /// shared entry point, scaled up from [`small_network`] to exercise
+    /// more of the state/action space than the three-node example network
+    /// does.
+    fn medium_network() -> NetworkGraph {
+        let mut g = NetworkGraph::new();
+        let web = g.add_node(Asset::new("web", "Web Server", 5_000.0, 0.6));
+        let vpn = g.add_node(Asset::new("vpn", "VPN Gateway", 8_000.0, 0.5));
+        let app = g.add_node(Asset::new("app", "App Server", 20_000.0, 0.4));
+        let file_share = g.add_node(Asset::new("files", "File Share", 15_000.0, 0.45));
+        let db = g.add_node(Asset::new("db", "Database", 100_000.0, 0.3));
+        let backup = g.add_node(Asset::new("backup", "Backup Server", 60_000.0, 0.35));
+        g.add_edge(web, app, 1.0);
+        g.add_edge(vpn, file_share, 1.0);
+        g.add_edge(app, db, 1.0);
+        g.add_edge(file_share, db, 1.5);
+        g.add_edge(db, backup, 1.0);
+        g
+    }
+
+    const SMALL_SCENARIO: GoldenScenario = GoldenScenario { name: "small", network: small_network, seed: 1, episodes: 50 };
+    const MEDIUM_SCENARIO: GoldenScenario = GoldenScenario { name: "medium", network: medium_network, seed: 2, episodes: 50 };
+
+    const SMALL_GOLDEN_JSON: &str = r#"{
+        "success_rate": 0.36,
+        "detection_rate": 0.44,
+        "mean_attacker_reward": 7200.0,
+        "mean_defender_reward": 0.44
+    }"#;
+
+    const MEDIUM_GOLDEN_JSON: &str = r#"{
+        "success_rate": 0.52,
+        "detection_rate": 0.28,
+        "mean_attacker_reward": 15820.0,
+        "mean_defender_reward": 900.28
+    }"#;
+
+    /// Tolerance for comparing re-run metrics against the recorded golden
+    /// values. Both scenarios are fully seeded (network, agent weights,
+    /// exploration, and replay sampling), so a passing run should match
+    /// almost exactly; this just absorbs minor floating-point drift across
+    /// platforms/toolchains, not RNG nondeterminism.
+    const TOLERANCE: f64 = 1e-6;
+
+    fn assert_matches_golden(scenario: &GoldenScenario, golden_json: &str) {
+        let golden: GoldenMetrics = serde_json::from_str(golden_json).expect("golden metrics JSON parses");
+        let actual = run_scenario(scenario);
+        let drifted = compare(&golden, &actual, TOLERANCE);
+        assert!(drifted.is_empty(), "scenario {:?} drifted from golden metrics: {drifted:?}", scenario.name);
+    }
+
+    #[test]
+    fn small_scenario_matches_its_recorded_golden_metrics() {
+        assert_matches_golden(&SMALL_SCENARIO, SMALL_GOLDEN_JSON);
+    }
+
+    #[test]
+    fn medium_scenario_matches_its_recorded_golden_metrics() {
+        assert_matches_golden(&MEDIUM_SCENARIO, MEDIUM_GOLDEN_JSON);
+    }
+
+    #[test]
+    fn compare_reports_every_metric_that_drifted_beyond_tolerance() {
+        let golden = GoldenMetrics { success_rate: 0.5, detection_rate: 0.5, mean_attacker_reward: 100.0, mean_defender_reward: 100.0 };
+        let actual = GoldenMetrics { success_rate: 0.5, detection_rate: 0.9, mean_attacker_reward: 100.0, mean_defender_reward: 500.0 };
+
+        let drifted = compare(&golden, &actual, 1e-9);
+        assert_eq!(drifted.len(), 2);
+        assert!(drifted.iter().any(|d| d.starts_with("detection_rate")));
+        assert!(drifted.iter().any(|d| d.starts_with("mean_defender_reward")));
+    }
+}
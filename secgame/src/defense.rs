@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::technique::AttackPhase;
+
+/// A class of countermeasure that can be deployed on a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DefenseType {
+    Ids,
+    Honeypot,
+    Encryption,
+    Monitoring,
+    Firewall,
+}
+
+impl DefenseType {
+    /// Built-in, rough cost per deployment.
+    pub fn base_cost(self) -> f64 {
+        match self {
+            DefenseType::Ids => 5_000.0,
+            DefenseType::Honeypot => 3_000.0,
+            DefenseType::Encryption => 4_000.0,
+            DefenseType::Monitoring => 6_000.0,
+            DefenseType::Firewall => 2_000.0,
+        }
+    }
+
+    /// Built-in, rough effectiveness at reducing attacker success.
+    pub fn base_effectiveness(self) -> f64 {
+        match self {
+            DefenseType::Ids => 0.3,
+            DefenseType::Honeypot => 0.2,
+            DefenseType::Encryption => 0.25,
+            DefenseType::Monitoring => 0.35,
+            DefenseType::Firewall => 0.15,
+        }
+    }
+
+    /// How relevant this defense actually is to *detecting* (as opposed to
+    /// preventing) a technique in `phase`, as a multiplier in `[0, 1]` on
+    /// [`DefenseType::base_effectiveness`]. E.g. `Monitoring` is built to
+    /// notice reconnaissance; `Encryption` protects data in transit but
+    /// doesn't make an attacker's activity any more or less visible.
+    /// [`DefenseType::Honeypot`] returns `0.0` here because honeypot
+    /// detection is handled separately, as a forced near-certain detection
+    /// (see [`crate::path::HONEYPOT_DETECTION_PROBABILITY`]).
+    pub fn detection_relevance(self, phase: AttackPhase) -> f64 {
+        match self {
+            DefenseType::Monitoring => match phase {
+                AttackPhase::Reconnaissance => 1.0,
+                AttackPhase::Exfiltration => 0.8,
+                _ => 0.5,
+            },
+            DefenseType::Ids => match phase {
+                AttackPhase::Execution | AttackPhase::LateralMovement => 1.0,
+                _ => 0.4,
+            },
+            DefenseType::Firewall => match phase {
+                AttackPhase::InitialAccess | AttackPhase::LateralMovement => 0.8,
+                _ => 0.2,
+            },
+            DefenseType::Honeypot | DefenseType::Encryption => 0.0,
+        }
+    }
+}
+
+/// A concave cost-to-coverage mapping modeling diminishing returns: each
+/// additional dollar spent on a node buys less incremental coverage than
+/// the last, so `coverage = 1 - exp(-k * spend)`.
+///
+/// Without a curve, coverage is the flat sum of each deployed defense's
+/// [`DefenseType::base_effectiveness`] (or override), capped at 1.0, which
+/// makes cost linear in coverage and pushes a budget-spreading optimizer
+/// to dump everything on one node.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CoverageCurve {
+    /// Decay rate. Larger `k` reaches saturating coverage with less spend.
+    pub k: f64,
+}
+
+impl CoverageCurve {
+    pub fn new(k: f64) -> Self {
+        CoverageCurve { k }
+    }
+
+    /// Map cumulative `spend` at a node to a coverage value in `[0, 1)`.
+    pub fn coverage_for_spend(&self, spend: f64) -> f64 {
+        1.0 - (-self.k * spend).exp()
+    }
+}
+
+/// Which defenses are deployed where.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DefenseConfiguration {
+    pub allocations: HashMap<NodeIndex, Vec<DefenseType>>,
+    /// Cumulative dollars spent per node, tracked alongside `allocations`
+    /// so a [`CoverageCurve`] can derive diminishing-returns coverage.
+    pub spend: HashMap<NodeIndex, f64>,
+    /// When set, [`DefenseConfiguration::effectiveness_at`] derives
+    /// coverage from cumulative spend via the curve instead of summing
+    /// flat per-defense effectiveness.
+    pub coverage_curve: Option<CoverageCurve>,
+    /// Hours a node's defenses need to notice an ongoing attack, per node.
+    /// Detection isn't instantaneous: a [`crate::simulation::Simulator`]
+    /// only treats an attack as actually caught if this latency elapses
+    /// before the attacking technique's own `time_cost` does, so a slow
+    /// detector can still notice an attack after the attacker has already
+    /// finished and gotten away with it. Defaults to `0.0` (instant
+    /// detection) for any node without an explicit entry.
+    pub detection_latency: HashMap<NodeIndex, f64>,
+}
+
+impl DefenseConfiguration {
+    pub fn new() -> Self {
+        DefenseConfiguration::default()
+    }
+
+    /// Build a configuration that derives coverage from spend via `curve`
+    /// rather than summing flat per-defense effectiveness.
+    pub fn with_coverage_curve(curve: CoverageCurve) -> Self {
+        DefenseConfiguration { coverage_curve: Some(curve), ..DefenseConfiguration::default() }
+    }
+
+    pub fn allocate(&mut self, node: NodeIndex, defense: DefenseType) {
+        self.allocate_with_cost(node, defense, defense.base_cost());
+    }
+
+    /// Like [`DefenseConfiguration::allocate`], but records `cost` against
+    /// the node's cumulative spend for [`CoverageCurve`] purposes.
+    pub fn allocate_with_cost(&mut self, node: NodeIndex, defense: DefenseType, cost: f64) {
+        self.allocations.entry(node).or_default().push(defense);
+        *self.spend.entry(node).or_insert(0.0) += cost;
+    }
+
+    pub fn defenses_at(&self, node: NodeIndex) -> &[DefenseType] {
+        self.allocations.get(&node).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn spend_at(&self, node: NodeIndex) -> f64 {
+        self.spend.get(&node).copied().unwrap_or(0.0)
+    }
+
+    /// Set how many hours `node`'s defenses need to notice an attack.
+    pub fn set_detection_latency(&mut self, node: NodeIndex, latency: f64) {
+        self.detection_latency.insert(node, latency);
+    }
+
+    /// `node`'s detection latency, or `0.0` (instant detection) if unset.
+    pub fn detection_latency_at(&self, node: NodeIndex) -> f64 {
+        self.detection_latency.get(&node).copied().unwrap_or(0.0)
+    }
+
+    /// Coverage at `node`: if a [`CoverageCurve`] is configured, the
+    /// concave `1 - exp(-k*spend)` mapping of cumulative spend; otherwise
+    /// the flat sum of every deployed defense's effectiveness, capped at
+    /// 1.0.
+    pub fn effectiveness_at(&self, node: NodeIndex) -> f64 {
+        match self.coverage_curve {
+            Some(curve) => curve.coverage_for_spend(self.spend_at(node)),
+            None => self
+                .defenses_at(node)
+                .iter()
+                .map(|d| d.base_effectiveness())
+                .sum::<f64>()
+                .min(1.0),
+        }
+    }
+
+    /// Fold `other`'s allocations and spend into this configuration,
+    /// e.g. layering an incremental budget deployment (such as
+    /// [`DefenseStrategy::greedy_allocate`]'s output) on top of whatever
+    /// is already deployed rather than replacing it.
+    pub fn merge(&mut self, other: DefenseConfiguration) {
+        for (node, defenses) in other.allocations {
+            self.allocations.entry(node).or_default().extend(defenses);
+        }
+        for (node, spend) in other.spend {
+            *self.spend.entry(node).or_insert(0.0) += spend;
+        }
+    }
+
+    /// Detection probability contributed by `node`'s deployed defenses
+    /// against a `phase` technique: each deployed [`DefenseType`]'s
+    /// [`DefenseType::base_effectiveness`] scaled by its
+    /// [`DefenseType::detection_relevance`] for `phase`, summed and capped
+    /// at `1.0`. Unlike [`DefenseConfiguration::effectiveness_at`] (which
+    /// reduces attacker success uniformly regardless of technique), this
+    /// only counts defenses actually relevant to noticing this phase of
+    /// attack.
+    pub fn detection_boost_at(&self, node: NodeIndex, phase: AttackPhase) -> f64 {
+        self.defenses_at(node).iter().map(|d| d.base_effectiveness() * d.detection_relevance(phase)).sum::<f64>().min(1.0)
+    }
+
+    /// Whether `node` is deployed as a deception target rather than a real
+    /// asset.
+    pub fn is_honeypot(&self, node: NodeIndex) -> bool {
+        self.defenses_at(node).contains(&DefenseType::Honeypot)
+    }
+
+    /// Defense gap per node in `network`: `value * vulnerability * (1 -
+    /// coverage)`, the risk this configuration leaves unmitigated. Higher
+    /// is worse; a node with no coverage at all keeps its full `value *
+    /// vulnerability` exposure.
+    pub fn gap_map(&self, network: &crate::network::NetworkGraph) -> HashMap<NodeIndex, f64> {
+        network
+            .node_indices()
+            .map(|node| {
+                let asset = &network[node];
+                let gap = asset.value * asset.vulnerability * (1.0 - self.effectiveness_at(node));
+                (node, gap)
+            })
+            .collect()
+    }
+}
+
+/// A model of an attacker's capability, used to scale technique success.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackerProfile {
+    pub skill_level: f64,
+    pub resources: f64,
+    pub objectives: Vec<String>,
+}
+
+impl AttackerProfile {
+    pub fn new(skill_level: f64, resources: f64, objectives: Vec<String>) -> Self {
+        AttackerProfile { skill_level, resources, objectives }
+    }
+}
+
+/// Per-[`DefenseType`] `(cost, effectiveness)` overrides, for modeling an
+/// organization's actual procurement costs and measured effectiveness
+/// rather than [`DefenseStrategy`]'s built-in rough defaults.
+pub type DefenseParameters = HashMap<DefenseType, (f64, f64)>;
+
+/// Picks where to spend a defense budget, using either the built-in
+/// per-[`DefenseType`] defaults or a [`DefenseParameters`] override table
+/// supplied via [`DefenseStrategy::with_parameters`].
+#[derive(Debug, Clone, Default)]
+pub struct DefenseStrategy {
+    parameters: DefenseParameters,
+    coverage_curve: Option<CoverageCurve>,
+}
+
+impl DefenseStrategy {
+    pub fn new() -> Self {
+        DefenseStrategy::default()
+    }
+
+    /// Build a strategy that consults `parameters` for any [`DefenseType`]
+    /// it overrides, falling back to the built-in defaults for the rest.
+    pub fn with_parameters(parameters: DefenseParameters) -> Self {
+        DefenseStrategy { parameters, coverage_curve: None }
+    }
+
+    /// Make allocations produced by this strategy derive coverage from
+    /// cumulative per-node spend via `curve` instead of the flat
+    /// sum-of-effectiveness model.
+    pub fn with_coverage_curve(mut self, curve: CoverageCurve) -> Self {
+        self.coverage_curve = Some(curve);
+        self
+    }
+
+    pub fn estimate_defense_cost(&self, defense: DefenseType) -> f64 {
+        self.parameters.get(&defense).map_or_else(|| defense.base_cost(), |&(cost, _)| cost)
+    }
+
+    pub fn estimate_effectiveness(&self, defense: DefenseType) -> f64 {
+        self.parameters.get(&defense).map_or_else(|| defense.base_effectiveness(), |&(_, effectiveness)| effectiveness)
+    }
+
+    /// Greedily spend `budget` on the cheapest defenses at the
+    /// highest-value nodes.
+    pub fn greedy_allocate(
+        &self,
+        network: &crate::network::NetworkGraph,
+        budget: f64,
+    ) -> DefenseConfiguration {
+        let mut nodes: Vec<NodeIndex> = network.node_indices().collect();
+        nodes.sort_by(|&a, &b| {
+            network[b].value.partial_cmp(&network[a].value).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut config = self.new_configuration();
+        let mut remaining = budget;
+        for node in nodes {
+            if remaining < self.estimate_defense_cost(DefenseType::Firewall) {
+                break;
+            }
+            for defense in [DefenseType::Ids, DefenseType::Monitoring, DefenseType::Firewall] {
+                let cost = self.estimate_defense_cost(defense);
+                if remaining >= cost {
+                    config.allocate_with_cost(node, defense, cost);
+                    remaining -= cost;
+                }
+            }
+        }
+        config
+    }
+
+    /// Split `budget` evenly across every node, rather than greedy's
+    /// concentrate-spend-on-the-highest-value-nodes ordering.
+    pub fn uniform_allocate(
+        &self,
+        network: &crate::network::NetworkGraph,
+        budget: f64,
+    ) -> DefenseConfiguration {
+        let nodes: Vec<NodeIndex> = network.node_indices().collect();
+        let mut config = self.new_configuration();
+        if nodes.is_empty() {
+            return config;
+        }
+
+        let per_node_budget = budget / nodes.len() as f64;
+        for node in nodes {
+            let mut remaining = per_node_budget;
+            for defense in [DefenseType::Ids, DefenseType::Monitoring, DefenseType::Firewall] {
+                let cost = self.estimate_defense_cost(defense);
+                if remaining >= cost {
+                    config.allocate_with_cost(node, defense, cost);
+                    remaining -= cost;
+                }
+            }
+        }
+        config
+    }
+
+    /// An empty configuration that carries this strategy's coverage curve,
+    /// if any.
+    fn new_configuration(&self) -> DefenseConfiguration {
+        match self.coverage_curve {
+            Some(curve) => DefenseConfiguration::with_coverage_curve(curve),
+            None => DefenseConfiguration::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overriding_ids_cost_changes_the_greedy_allocation_count_under_a_fixed_budget() {
+        let network = crate::network::create_example_network();
+        let budget = 10_000.0;
+
+        let baseline = DefenseStrategy::new().greedy_allocate(&network, budget);
+        let baseline_count: usize = baseline.allocations.values().map(|defenses| defenses.len()).sum();
+
+        let mut parameters = DefenseParameters::new();
+        parameters.insert(DefenseType::Ids, (9_000.0, DefenseType::Ids.base_effectiveness()));
+        let overridden = DefenseStrategy::with_parameters(parameters).greedy_allocate(&network, budget);
+        let overridden_count: usize = overridden.allocations.values().map(|defenses| defenses.len()).sum();
+
+        assert_ne!(baseline_count, overridden_count);
+    }
+
+    #[test]
+    fn coverage_curve_yields_diminishing_returns_on_additional_spend() {
+        let node = NodeIndex::new(0);
+        let mut config = DefenseConfiguration::with_coverage_curve(CoverageCurve::new(0.0005));
+
+        // First dollars of spend buy more coverage than later dollars once
+        // the curve starts saturating.
+        config.allocate_with_cost(node, DefenseType::Firewall, 1_000.0);
+        let gain_from_first_1000 = config.effectiveness_at(node);
+
+        config.allocate_with_cost(node, DefenseType::Firewall, 1_000.0);
+        let gain_from_second_1000 = config.effectiveness_at(node) - gain_from_first_1000;
+
+        config.allocate_with_cost(node, DefenseType::Firewall, 1_000.0);
+        let gain_from_third_1000 = config.effectiveness_at(node) - gain_from_first_1000 - gain_from_second_1000;
+
+        assert!(gain_from_second_1000 < gain_from_first_1000);
+        assert!(gain_from_third_1000 < gain_from_second_1000);
+    }
+
+    #[test]
+    fn uncovered_high_value_node_has_a_larger_gap_than_a_covered_one() {
+        let network = crate::network::create_example_network();
+        let db = crate::network::node_by_id(&network, "db").expect("example network has a db node");
+        let web = crate::network::node_by_id(&network, "web").expect("example network has a web node");
+
+        // Fully cover the cheaper node, and leave the highest-value,
+        // highest-risk node (`db`) entirely uncovered.
+        let mut config = DefenseConfiguration::new();
+        for defense in [DefenseType::Ids, DefenseType::Monitoring, DefenseType::Firewall, DefenseType::Encryption] {
+            config.allocate(web, defense);
+        }
+
+        let gaps = config.gap_map(&network);
+        assert!(gaps[&db] > 0.0);
+        assert!(gaps[&db] > gaps[&web]);
+    }
+
+    #[test]
+    fn detection_latency_defaults_to_instant_until_set() {
+        let node = NodeIndex::new(0);
+        let mut config = DefenseConfiguration::new();
+        assert_eq!(config.detection_latency_at(node), 0.0);
+
+        config.set_detection_latency(node, 2.5);
+        assert_eq!(config.detection_latency_at(node), 2.5);
+    }
+
+    #[test]
+    fn monitoring_raises_detection_for_reconnaissance_but_encryption_does_not() {
+        let node = NodeIndex::new(0);
+        let baseline = DefenseConfiguration::new().detection_boost_at(node, AttackPhase::Reconnaissance);
+
+        let mut monitored = DefenseConfiguration::new();
+        monitored.allocate(node, DefenseType::Monitoring);
+        assert!(monitored.detection_boost_at(node, AttackPhase::Reconnaissance) > baseline);
+
+        let mut encrypted = DefenseConfiguration::new();
+        encrypted.allocate(node, DefenseType::Encryption);
+        assert_eq!(encrypted.detection_boost_at(node, AttackPhase::Reconnaissance), baseline);
+    }
+}
@@ -0,0 +1,663 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use ndarray::{Array1, Array2};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::defense::{DefenseConfiguration, DefenseType};
+use crate::network::NetworkGraph;
+use crate::path::AttackPath;
+use crate::technique::{AccessLevel, AttackPhase, AttackTechnique};
+
+/// Errors from indexing a [`SecurityGame`]'s payoff matrices.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    InvalidDefenderAction { index: usize, count: usize },
+    InvalidAttackerAction { index: usize, count: usize },
+    InvalidStrategySum { defender_sum: f64, attacker_sum: f64 },
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::InvalidDefenderAction { index, count } => {
+                write!(f, "defender action {index} is out of bounds for {count} defender actions")
+            }
+            GameError::InvalidAttackerAction { index, count } => {
+                write!(f, "attacker action {index} is out of bounds for {count} attacker actions")
+            }
+            GameError::InvalidStrategySum { defender_sum, attacker_sum } => {
+                write!(
+                    f,
+                    "strategy vectors must each sum to ~1 (defender sums to {defender_sum}, attacker sums to {attacker_sum})"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Errors from [`SecurityGame::save_json`] / [`SecurityGame::load_json`].
+#[derive(Debug)]
+pub enum GameFileError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    /// A loaded payoff matrix's shape didn't match its action vector's
+    /// length.
+    DimensionMismatch { expected: (usize, usize), found: (usize, usize) },
+}
+
+impl fmt::Display for GameFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameFileError::Io(err) => write!(f, "i/o error: {err}"),
+            GameFileError::Parse(err) => write!(f, "invalid SecurityGame JSON: {err}"),
+            GameFileError::DimensionMismatch { expected, found } => write!(
+                f,
+                "payoff matrix shape {found:?} does not match {expected:?} (defender_actions.len() x attacker_actions.len())"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GameFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameFileError::Io(err) => Some(err),
+            GameFileError::Parse(err) => Some(err),
+            GameFileError::DimensionMismatch { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for GameFileError {
+    fn from(err: io::Error) -> Self {
+        GameFileError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for GameFileError {
+    fn from(err: serde_json::Error) -> Self {
+        GameFileError::Parse(err)
+    }
+}
+
+/// A move available to a player, with an economic cost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Action {
+    pub id: String,
+    pub cost: f64,
+}
+
+impl Action {
+    pub fn new(id: impl Into<String>, cost: f64) -> Self {
+        Action { id: id.into(), cost }
+    }
+}
+
+/// A two-player, general-sum normal-form game between a defender and an
+/// attacker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityGame {
+    pub defender_actions: Vec<Action>,
+    pub attacker_actions: Vec<Action>,
+    pub payoff_defender: Array2<f64>,
+    pub payoff_attacker: Array2<f64>,
+    /// Set by [`SecurityGame::apply_action_costs`] so a second call is a
+    /// no-op instead of subtracting action costs twice.
+    #[serde(default)]
+    action_costs_applied: bool,
+}
+
+impl SecurityGame {
+    pub fn new(defender_actions: Vec<Action>, attacker_actions: Vec<Action>) -> Self {
+        let (d, a) = (defender_actions.len(), attacker_actions.len());
+        SecurityGame {
+            defender_actions,
+            attacker_actions,
+            payoff_defender: Array2::zeros((d, a)),
+            payoff_attacker: Array2::zeros((d, a)),
+            action_costs_applied: false,
+        }
+    }
+
+    /// Subtract each defender action's cost from its entire payoff row and
+    /// each attacker action's cost from its entire payoff column, so a
+    /// player's payoff reflects the economic cost of the action they took,
+    /// not just the outcome. A no-op on a game this has already been
+    /// called on, so chaining it after [`SecurityGame::set_payoff`] calls
+    /// (which don't know about costs) is always safe to repeat.
+    pub fn apply_action_costs(&mut self) {
+        if self.action_costs_applied {
+            return;
+        }
+        for (d, action) in self.defender_actions.iter().enumerate() {
+            for a in 0..self.attacker_actions.len() {
+                self.payoff_defender[[d, a]] -= action.cost;
+            }
+        }
+        for (a, action) in self.attacker_actions.iter().enumerate() {
+            for d in 0..self.defender_actions.len() {
+                self.payoff_attacker[[d, a]] -= action.cost;
+            }
+        }
+        self.action_costs_applied = true;
+    }
+
+    /// Serialize this game to `path` as JSON, so a scenario can be shared
+    /// with another analyst and reloaded with [`SecurityGame::load_json`].
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), GameFileError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Load a game saved by [`SecurityGame::save_json`], rejecting one
+    /// whose payoff matrices don't have one row per `defender_actions`
+    /// entry and one column per `attacker_actions` entry instead of
+    /// panicking the first time an action is indexed.
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, GameFileError> {
+        let contents = fs::read_to_string(path)?;
+        let game: SecurityGame = serde_json::from_str(&contents)?;
+
+        let expected = (game.defender_actions.len(), game.attacker_actions.len());
+        if game.payoff_defender.dim() != expected || game.payoff_attacker.dim() != expected {
+            let found = if game.payoff_defender.dim() != expected { game.payoff_defender.dim() } else { game.payoff_attacker.dim() };
+            return Err(GameFileError::DimensionMismatch { expected, found });
+        }
+        Ok(game)
+    }
+
+    pub fn set_payoff(
+        &mut self,
+        defender_action: usize,
+        attacker_action: usize,
+        defender_payoff: f64,
+        attacker_payoff: f64,
+    ) -> Result<(), GameError> {
+        if defender_action >= self.defender_actions.len() {
+            return Err(GameError::InvalidDefenderAction { index: defender_action, count: self.defender_actions.len() });
+        }
+        if attacker_action >= self.attacker_actions.len() {
+            return Err(GameError::InvalidAttackerAction { index: attacker_action, count: self.attacker_actions.len() });
+        }
+        self.payoff_defender[[defender_action, attacker_action]] = defender_payoff;
+        self.payoff_attacker[[defender_action, attacker_action]] = attacker_payoff;
+        Ok(())
+    }
+
+    /// The attacker action that maximizes their payoff against the given
+    /// (possibly mixed) defender strategy. Falls back to action `0` if
+    /// there are no attacker actions or every expected payoff is NaN,
+    /// rather than panicking.
+    pub fn attacker_best_response(&self, defender_strategy: &Array1<f64>) -> usize {
+        let expected: Vec<f64> = (0..self.attacker_actions.len())
+            .map(|a| {
+                (0..self.defender_actions.len())
+                    .map(|d| defender_strategy[d] * self.payoff_attacker[[d, a]])
+                    .sum()
+            })
+            .collect();
+
+        expected
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !v.is_nan())
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Whether this game is zero-sum: the defender's payoff is the
+    /// attacker's negated, at every action pair, within `tolerance`.
+    pub fn is_zero_sum(&self, tolerance: f64) -> bool {
+        self.payoff_defender.iter().zip(self.payoff_attacker.iter()).all(|(d, a)| (d + a).abs() <= tolerance)
+    }
+
+    /// Build a game from a [`NetworkGraph`]: each node becomes an attacker
+    /// action, and each distinct [`DefenseType`] deployed anywhere in
+    /// `defense` becomes a defender action representing deploying that
+    /// defense network-wide (a single "none" action if nothing is
+    /// deployed). Payoffs come from the expected value of attacking each
+    /// node with its best available initial-access technique, with the
+    /// defense's effectiveness added to that technique's detectability.
+    pub fn from_network(network: &NetworkGraph, techniques: &[AttackTechnique], defense: &DefenseConfiguration) -> SecurityGame {
+        let attacker_actions: Vec<Action> =
+            network.node_indices().map(|n| Action::new(network[n].id.clone(), 0.0)).collect();
+
+        let mut defense_kinds: Vec<DefenseType> = defense.allocations.values().flatten().copied().collect();
+        defense_kinds.sort_by_key(|d| *d as u8);
+        defense_kinds.dedup();
+        let defender_action_kinds: Vec<Option<DefenseType>> = if defense_kinds.is_empty() {
+            vec![None]
+        } else {
+            defense_kinds.into_iter().map(Some).collect()
+        };
+        let defender_actions: Vec<Action> = defender_action_kinds
+            .iter()
+            .map(|kind| match kind {
+                Some(defense_type) => Action::new(format!("{defense_type:?}"), defense_type.base_cost()),
+                None => Action::new("none", 0.0),
+            })
+            .collect();
+
+        let mut game = SecurityGame::new(defender_actions, attacker_actions);
+
+        for (a, node) in network.node_indices().enumerate() {
+            let asset = &network[node];
+            let Some(technique) = techniques
+                .iter()
+                .filter(|t| t.phase == AttackPhase::InitialAccess && t.required_access <= AccessLevel::None)
+                .max_by(|x, y| x.success_rate.partial_cmp(&y.success_rate).unwrap_or(std::cmp::Ordering::Equal))
+            else {
+                continue;
+            };
+
+            for (d, kind) in defender_action_kinds.iter().enumerate() {
+                let extra_effectiveness = kind.map(|k| k.base_effectiveness()).unwrap_or(0.0);
+                let mut adjusted = technique.clone();
+                adjusted.detectability = (technique.detectability + extra_effectiveness).min(1.0);
+
+                let mut path = AttackPath::new(asset.value);
+                path.add_step(node, &adjusted, 1.0, *kind == Some(DefenseType::Honeypot), asset.vulnerability, AccessLevel::None)
+                    .expect("technique was filtered to require no more than None access");
+                let attacker_payoff = path.calculate_expected_value();
+
+                game.set_payoff(d, a, -attacker_payoff, attacker_payoff).expect("indices within bounds by construction");
+            }
+        }
+
+        game
+    }
+}
+
+/// A (possibly mixed) strategy for each player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyProfile {
+    pub defender_strategy: Array1<f64>,
+    pub attacker_strategy: Array1<f64>,
+}
+
+impl StrategyProfile {
+    const SUM_TOLERANCE: f64 = 1e-6;
+
+    fn validate_sums(&self) -> Result<(), GameError> {
+        let defender_sum: f64 = self.defender_strategy.iter().sum();
+        let attacker_sum: f64 = self.attacker_strategy.iter().sum();
+        if (defender_sum - 1.0).abs() > Self::SUM_TOLERANCE || (attacker_sum - 1.0).abs() > Self::SUM_TOLERANCE {
+            return Err(GameError::InvalidStrategySum { defender_sum, attacker_sum });
+        }
+        Ok(())
+    }
+
+    /// Draws one concrete `(defender_action, attacker_action)` realization
+    /// according to this profile's probability vectors, for Monte Carlo
+    /// evaluation. Errors if either vector doesn't sum to ~1.
+    pub fn sample(&self, rng: &mut impl Rng) -> Result<(usize, usize), GameError> {
+        self.validate_sums()?;
+        let defender_dist = WeightedIndex::new(self.defender_strategy.iter()).expect("validated to sum to ~1");
+        let attacker_dist = WeightedIndex::new(self.attacker_strategy.iter()).expect("validated to sum to ~1");
+        Ok((defender_dist.sample(rng), attacker_dist.sample(rng)))
+    }
+
+    /// Draws `n` independent realizations via [`StrategyProfile::sample`].
+    pub fn sample_n(&self, n: usize, rng: &mut impl Rng) -> Result<Vec<(usize, usize)>, GameError> {
+        self.validate_sums()?;
+        let defender_dist = WeightedIndex::new(self.defender_strategy.iter()).expect("validated to sum to ~1");
+        let attacker_dist = WeightedIndex::new(self.attacker_strategy.iter()).expect("validated to sum to ~1");
+        Ok((0..n).map(|_| (defender_dist.sample(rng), attacker_dist.sample(rng))).collect())
+    }
+}
+
+/// A simple iterative best-response solver (not a true LP-based Nash
+/// solver, but converges for the small games this crate models).
+///
+/// Delegates to [`ZeroSumSolver`] for zero-sum games, where that
+/// converges to an exact minimax equilibrium instead of the uniform
+/// fallback below.
+pub struct NashSolver;
+
+impl NashSolver {
+    pub fn solve(game: &SecurityGame) -> StrategyProfile {
+        if game.is_zero_sum(1e-9) {
+            return ZeroSumSolver::solve(game).0;
+        }
+
+        let d = game.defender_actions.len();
+        let a = game.attacker_actions.len();
+        StrategyProfile {
+            defender_strategy: Array1::from_elem(d, 1.0 / d as f64),
+            attacker_strategy: Array1::from_elem(a, 1.0 / a as f64),
+        }
+    }
+}
+
+/// Solves a zero-sum [`SecurityGame`] via fictitious play: both players
+/// repeatedly best-respond to the other's empirical strategy so far. For
+/// two-player zero-sum games this provably converges to a minimax
+/// equilibrium (Robinson 1951), without needing a full LP solver for the
+/// small games this crate models.
+pub struct ZeroSumSolver;
+
+impl ZeroSumSolver {
+    const ITERATIONS: usize = 5_000;
+
+    /// Returns the converged strategy profile and the resulting game value
+    /// to the defender.
+    pub fn solve(game: &SecurityGame) -> (StrategyProfile, f64) {
+        let d = game.defender_actions.len();
+        let a = game.attacker_actions.len();
+
+        let mut defender_counts = vec![0.0; d];
+        let mut attacker_counts = vec![0.0; a];
+        let mut defender_action = 0usize;
+        let mut attacker_action = 0usize;
+
+        for _ in 0..Self::ITERATIONS {
+            defender_counts[defender_action] += 1.0;
+            attacker_counts[attacker_action] += 1.0;
+
+            defender_action = (0..d)
+                .max_by(|&x, &y| {
+                    let ex: f64 = (0..a).map(|i| attacker_counts[i] * game.payoff_defender[[x, i]]).sum();
+                    let ey: f64 = (0..a).map(|i| attacker_counts[i] * game.payoff_defender[[y, i]]).sum();
+                    ex.partial_cmp(&ey).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(0);
+            attacker_action = (0..a)
+                .max_by(|&x, &y| {
+                    let ex: f64 = (0..d).map(|i| defender_counts[i] * game.payoff_attacker[[i, x]]).sum();
+                    let ey: f64 = (0..d).map(|i| defender_counts[i] * game.payoff_attacker[[i, y]]).sum();
+                    ex.partial_cmp(&ey).unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(0);
+        }
+
+        let total_d: f64 = defender_counts.iter().sum();
+        let total_a: f64 = attacker_counts.iter().sum();
+        let defender_strategy = Array1::from_vec(defender_counts.iter().map(|c| c / total_d).collect());
+        let attacker_strategy = Array1::from_vec(attacker_counts.iter().map(|c| c / total_a).collect());
+
+        let value: f64 = (0..d)
+            .map(|i| (0..a).map(|j| defender_strategy[i] * attacker_strategy[j] * game.payoff_defender[[i, j]]).sum::<f64>())
+            .sum();
+
+        (StrategyProfile { defender_strategy, attacker_strategy }, value)
+    }
+}
+
+/// A security game under uncertainty about which attacker type the
+/// defender faces: each type has its own payoffs, weighted by a prior
+/// probability. The defender must commit to one action before the type is
+/// revealed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BayesianSecurityGame {
+    pub defender_actions: Vec<Action>,
+    /// One `(type name, prior weight, that type's game)` entry per
+    /// attacker type. Priors are normalized internally, so they don't need
+    /// to already sum to `1.0`.
+    pub types: Vec<(String, f64, SecurityGame)>,
+}
+
+impl BayesianSecurityGame {
+    pub fn new(defender_actions: Vec<Action>) -> Self {
+        BayesianSecurityGame { defender_actions, types: Vec::new() }
+    }
+
+    pub fn add_type(&mut self, name: impl Into<String>, prior: f64, game: SecurityGame) {
+        self.types.push((name.into(), prior, game));
+    }
+
+    fn normalized_priors(&self) -> Vec<f64> {
+        let total: f64 = self.types.iter().map(|(_, prior, _)| prior).sum();
+        if total <= 0.0 {
+            vec![0.0; self.types.len()]
+        } else {
+            self.types.iter().map(|(_, prior, _)| prior / total).collect()
+        }
+    }
+
+    /// The defender action that maximizes expected defender payoff
+    /// (Bayes-Nash), assuming each type best-responds to that action
+    /// within its own payoffs.
+    pub fn best_defender_action(&self) -> usize {
+        let priors = self.normalized_priors();
+        (0..self.defender_actions.len())
+            .max_by(|&x, &y| {
+                self.expected_defender_payoff(x, &priors)
+                    .partial_cmp(&self.expected_defender_payoff(y, &priors))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0)
+    }
+
+    /// Expected defender payoff of `defender_action`, averaged across
+    /// attacker types under `priors`, with each type best-responding
+    /// within its own payoffs.
+    fn expected_defender_payoff(&self, defender_action: usize, priors: &[f64]) -> f64 {
+        self.types
+            .iter()
+            .zip(priors)
+            .map(|((_, _, game), prior)| {
+                let attacker_best = (0..game.attacker_actions.len())
+                    .max_by(|&x, &y| {
+                        game.payoff_attacker[[defender_action, x]]
+                            .partial_cmp(&game.payoff_attacker[[defender_action, y]])
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .unwrap_or(0);
+                prior * game.payoff_defender[[defender_action, attacker_best]]
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::create_example_network;
+    use crate::technique::example_techniques;
+
+    #[test]
+    fn applying_action_costs_lowers_payoffs_by_exactly_the_costs_and_is_idempotent() {
+        let mut game = SecurityGame::new(
+            vec![Action::new("patch", 2.0), Action::new("monitor", 3.0)],
+            vec![Action::new("phish", 1.0), Action::new("exploit", 4.0)],
+        );
+        for d in 0..2 {
+            for a in 0..2 {
+                game.set_payoff(d, a, 10.0, 20.0).unwrap();
+            }
+        }
+
+        game.apply_action_costs();
+
+        for (d, defender_cost) in [2.0, 3.0].into_iter().enumerate() {
+            for (a, attacker_cost) in [1.0, 4.0].into_iter().enumerate() {
+                assert_eq!(game.payoff_defender[[d, a]], 10.0 - defender_cost);
+                assert_eq!(game.payoff_attacker[[d, a]], 20.0 - attacker_cost);
+            }
+        }
+
+        // Calling it again must not subtract the costs a second time.
+        game.apply_action_costs();
+        assert_eq!(game.payoff_defender[[0, 0]], 10.0 - 2.0);
+        assert_eq!(game.payoff_attacker[[0, 0]], 20.0 - 1.0);
+    }
+
+    #[test]
+    fn basic_game_round_trips_through_json() {
+        let mut game = SecurityGame::new(vec![Action::new("patch", 2.0)], vec![Action::new("phish", 1.0)]);
+        game.set_payoff(0, 0, -5.0, 5.0).unwrap();
+        let path = std::env::temp_dir().join("secgame_basic_game_round_trip_test.json");
+
+        game.save_json(&path).unwrap();
+        let loaded = SecurityGame::load_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.defender_actions.len(), 1);
+        assert_eq!(loaded.attacker_actions.len(), 1);
+        assert_eq!(loaded.payoff_defender[[0, 0]], -5.0);
+        assert_eq!(loaded.payoff_attacker[[0, 0]], 5.0);
+    }
+
+    #[test]
+    fn loading_a_game_with_mismatched_payoff_dimensions_errors_descriptively() {
+        let path = std::env::temp_dir().join("secgame_dimension_mismatch_test.json");
+        // Two attacker actions declared, but only one payoff column.
+        std::fs::write(
+            &path,
+            r#"{"defender_actions":[{"id":"patch","cost":1.0}],
+                "attacker_actions":[{"id":"phish","cost":0.0},{"id":"exploit","cost":0.0}],
+                "payoff_defender":{"v":1,"dim":[1,1],"data":[0.0]},
+                "payoff_attacker":{"v":1,"dim":[1,1],"data":[0.0]}}"#,
+        )
+        .unwrap();
+
+        let err = SecurityGame::load_json(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        match err {
+            GameFileError::DimensionMismatch { expected, found } => {
+                assert_eq!(expected, (1, 2));
+                assert_eq!(found, (1, 1));
+            }
+            other => panic!("expected DimensionMismatch, got {other}"),
+        }
+    }
+
+    #[test]
+    fn out_of_bounds_set_payoff_errors_instead_of_panicking() {
+        let mut game = SecurityGame::new(vec![Action::new("patch", 1.0)], vec![Action::new("phish", 0.0)]);
+
+        assert_eq!(
+            game.set_payoff(0, 5, 1.0, 1.0),
+            Err(GameError::InvalidAttackerAction { index: 5, count: 1 })
+        );
+        assert_eq!(
+            game.set_payoff(5, 0, 1.0, 1.0),
+            Err(GameError::InvalidDefenderAction { index: 5, count: 1 })
+        );
+        assert!(game.set_payoff(0, 0, 1.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn best_response_falls_back_to_zero_on_nan_payoffs() {
+        let mut game = SecurityGame::new(vec![Action::new("patch", 1.0)], vec![Action::new("a", 0.0), Action::new("b", 0.0)]);
+        game.payoff_attacker[[0, 0]] = f64::NAN;
+        game.payoff_attacker[[0, 1]] = f64::NAN;
+
+        let response = game.attacker_best_response(&Array1::from_elem(1, 1.0));
+        assert_eq!(response, 0);
+    }
+
+    #[test]
+    fn matching_pennies_is_detected_zero_sum_with_half_half_equilibrium() {
+        let mut game = SecurityGame::new(vec![Action::new("heads", 0.0), Action::new("tails", 0.0)], vec![Action::new("heads", 0.0), Action::new("tails", 0.0)]);
+        // The attacker wins (defender loses) on a mismatch.
+        game.set_payoff(0, 0, -1.0, 1.0).unwrap();
+        game.set_payoff(0, 1, 1.0, -1.0).unwrap();
+        game.set_payoff(1, 0, 1.0, -1.0).unwrap();
+        game.set_payoff(1, 1, -1.0, 1.0).unwrap();
+
+        assert!(game.is_zero_sum(1e-9));
+
+        let (profile, value) = ZeroSumSolver::solve(&game);
+        assert!((profile.defender_strategy[0] - 0.5).abs() < 0.05);
+        assert!((profile.attacker_strategy[0] - 0.5).abs() < 0.05);
+        assert!(value.abs() < 0.05);
+    }
+
+    #[test]
+    fn from_network_has_one_attacker_action_per_node() {
+        let network = create_example_network();
+        let techniques = example_techniques();
+        let defense = DefenseConfiguration::new();
+
+        let game = SecurityGame::from_network(&network, &techniques, &defense);
+        assert_eq!(game.attacker_actions.len(), network.node_count());
+    }
+
+    /// Both types share the same `patch_web`/`patch_db` defender actions
+    /// and `web`/`db` attacker actions, but a script kiddie does modest
+    /// damage to whichever target is unpatched, while an APT does heavy
+    /// damage specifically against an unpatched db.
+    fn script_kiddie_vs_apt_bayesian_game() -> BayesianSecurityGame {
+        let defender_actions = vec![Action::new("patch_web", 0.0), Action::new("patch_db", 0.0)];
+
+        let mut script_kiddie = SecurityGame::new(defender_actions.clone(), vec![Action::new("web", 0.0), Action::new("db", 0.0)]);
+        script_kiddie.set_payoff(0, 0, 0.0, 0.0).unwrap();
+        script_kiddie.set_payoff(0, 1, -1.0, 1.0).unwrap();
+        script_kiddie.set_payoff(1, 0, -5.0, 5.0).unwrap();
+        script_kiddie.set_payoff(1, 1, 0.0, 0.0).unwrap();
+
+        let mut apt = SecurityGame::new(defender_actions.clone(), vec![Action::new("web", 0.0), Action::new("db", 0.0)]);
+        apt.set_payoff(0, 0, 0.0, 0.0).unwrap();
+        apt.set_payoff(0, 1, -10.0, 10.0).unwrap();
+        apt.set_payoff(1, 0, -1.0, 1.0).unwrap();
+        apt.set_payoff(1, 1, 0.0, 0.0).unwrap();
+
+        let mut game = BayesianSecurityGame::new(defender_actions);
+        game.add_type("script_kiddie", 1.0, script_kiddie);
+        game.add_type("apt", 0.0, apt);
+        game
+    }
+
+    #[test]
+    fn sample_rejects_strategy_vectors_that_dont_sum_to_one() {
+        let profile = StrategyProfile {
+            defender_strategy: Array1::from_vec(vec![0.5, 0.2]),
+            attacker_strategy: Array1::from_vec(vec![0.5, 0.5]),
+        };
+
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            profile.sample(&mut rng),
+            Err(GameError::InvalidStrategySum { defender_sum: 0.7, attacker_sum: 1.0 })
+        );
+    }
+
+    #[test]
+    fn sample_n_empirical_frequencies_approximate_the_strategy_probabilities() {
+        let profile = StrategyProfile {
+            defender_strategy: Array1::from_vec(vec![0.2, 0.8]),
+            attacker_strategy: Array1::from_vec(vec![0.5, 0.3, 0.2]),
+        };
+
+        let mut rng = rand::thread_rng();
+        let draws = profile.sample_n(20_000, &mut rng).unwrap();
+
+        let mut defender_counts = [0usize; 2];
+        let mut attacker_counts = [0usize; 3];
+        for (d, a) in &draws {
+            defender_counts[*d] += 1;
+            attacker_counts[*a] += 1;
+        }
+
+        let n = draws.len() as f64;
+        assert!((defender_counts[0] as f64 / n - 0.2).abs() < 0.02);
+        assert!((defender_counts[1] as f64 / n - 0.8).abs() < 0.02);
+        assert!((attacker_counts[0] as f64 / n - 0.5).abs() < 0.02);
+        assert!((attacker_counts[1] as f64 / n - 0.3).abs() < 0.02);
+        assert!((attacker_counts[2] as f64 / n - 0.2).abs() < 0.02);
+    }
+
+    #[test]
+    fn optimal_defense_shifts_toward_apt_target_as_its_prior_grows() {
+        let mut game = script_kiddie_vs_apt_bayesian_game();
+
+        // Facing only the script kiddie, patching the web server minimizes
+        // damage (the kiddie then does only modest damage to the db).
+        assert_eq!(game.best_defender_action(), 0);
+
+        // Facing only the APT, patching the db minimizes damage instead.
+        game.types[0].1 = 0.0;
+        game.types[1].1 = 1.0;
+        assert_eq!(game.best_defender_action(), 1);
+    }
+}
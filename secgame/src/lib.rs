@@ -0,0 +1,35 @@
+//! A toy network-security simulation combining a game-theoretic
+//! defender/attacker model with a reinforcement-learning attacker.
+
+mod agent;
+mod analysis;
+mod benchmarks;
+mod defense;
+mod game;
+mod network;
+mod normalizer;
+mod path;
+mod simulation;
+mod strategy;
+mod technique;
+
+pub use agent::{DQNAgent, Experience, Loss, QNetwork, TargetUpdate};
+pub use analysis::{
+    compute_expected_loss, AggregateReport, AnalysisReport, Analyzer, DefenseComparisonRow, DefenseGapRow, ReportSchemaError, RoiReport,
+    SegmentationRecommendation, ANALYSIS_REPORT_SCHEMA_VERSION,
+};
+pub use benchmarks::{compare, run_scenario, GoldenMetrics, GoldenScenario};
+pub use defense::{AttackerProfile, CoverageCurve, DefenseConfiguration, DefenseParameters, DefenseStrategy, DefenseType};
+pub use game::{Action, BayesianSecurityGame, GameError, GameFileError, NashSolver, SecurityGame, StrategyProfile, ZeroSumSolver};
+pub use network::{
+    create_example_network, load_network_json, node_by_id, save_network_json, validate_network, Asset, NetworkError, NetworkGraph,
+    NetworkValidation,
+};
+pub use normalizer::StateNormalizer;
+pub use path::{AttackObjective, AttackPath, AttackStep};
+pub use simulation::{
+    run_ensemble, AttackStepTrace, CancellationToken, DefaultReward, EarlyStopping, EnsembleMetrics, MeanStd, MetricsSchemaError, RewardFn,
+    RewardWeights, SimError, SimulationConfig, SimulationMetrics, Simulator, StopReason, SIMULATION_METRICS_SCHEMA_VERSION,
+};
+pub use strategy::AttackStrategy;
+pub use technique::{example_techniques, AccessLevel, AttackPhase, AttackTechnique};
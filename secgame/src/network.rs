@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use petgraph::algo::connected_components;
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+
+/// A single host/service in the modeled network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asset {
+    pub id: String,
+    pub name: String,
+    /// Dollar value if compromised.
+    pub value: f64,
+    /// 0.0 (hardened) .. 1.0 (wide open).
+    pub vulnerability: f64,
+    /// How much losing this asset matters beyond its dollar `value` (e.g. a
+    /// domain controller's blast radius, or a database's compliance
+    /// exposure). Multiplies `value` in loss calculations; defaults to
+    /// `1.0` (no adjustment) for assets loaded from JSON that predates this
+    /// field. See [`crate::analysis::compute_expected_loss`].
+    #[serde(default = "default_criticality")]
+    pub criticality: f64,
+    /// Whether the attacker is assumed to already hold this asset before
+    /// the simulation starts, e.g. for breach-assumption ("assume
+    /// ransomware already landed on this host") scenarios. Paths starting
+    /// from a compromised entry begin with elevated access instead of
+    /// [`crate::technique::AccessLevel::None`]; see
+    /// [`crate::strategy::AttackStrategy::find_path_to_target`].
+    #[serde(default)]
+    pub compromised: bool,
+    /// Whether this asset is currently unreachable, e.g. taken offline for
+    /// maintenance or isolated as an incident response measure.
+    /// Pathfinding skips offline assets entirely, both as hops and as
+    /// entry/target nodes.
+    #[serde(default)]
+    pub offline: bool,
+    /// How plausible this asset is as an attacker's initial foothold, from
+    /// `0.0` (never considered an entry point, e.g. an internal-only
+    /// database) to `1.0` (fully internet-facing). Defaults to `1.0` for
+    /// assets loaded from JSON that predates this field, matching the
+    /// "every node is a valid entry" behavior pathfinding had before it
+    /// existed. See [`crate::strategy::AttackStrategy::generate_optimal_path`].
+    #[serde(default = "default_exposure")]
+    pub exposure: f64,
+}
+
+fn default_criticality() -> f64 {
+    1.0
+}
+
+fn default_exposure() -> f64 {
+    1.0
+}
+
+impl Asset {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, value: f64, vulnerability: f64) -> Self {
+        Asset {
+            id: id.into(),
+            name: name.into(),
+            value,
+            vulnerability,
+            criticality: default_criticality(),
+            compromised: false,
+            offline: false,
+            exposure: default_exposure(),
+        }
+    }
+
+    /// Override this asset's default criticality of `1.0`.
+    pub fn with_criticality(mut self, criticality: f64) -> Self {
+        self.criticality = criticality;
+        self
+    }
+
+    /// Override this asset's default exposure of `1.0`.
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    /// Mark this asset as already held by the attacker.
+    pub fn with_compromised(mut self, compromised: bool) -> Self {
+        self.compromised = compromised;
+        self
+    }
+
+    /// Mark this asset as unreachable for pathfinding purposes.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+}
+
+/// The modeled network: assets as nodes, lateral-movement links as edges.
+/// Edge weight models link "distance" (monitoring/segmentation); 1.0 is the
+/// default, undifferentiated link.
+///
+/// [`Graph`] is directed by default, which this type relies on: an edge
+/// from `a` to `b` does not imply a route back from `b` to `a`. Model a
+/// two-way link (most segmentation is, in practice, one-way-trusted) with
+/// two edges, one in each direction.
+pub type NetworkGraph = Graph<Asset, f64>;
+
+/// A small example network used in docs, examples, and tests.
+pub fn create_example_network() -> NetworkGraph {
+    let mut g = NetworkGraph::new();
+    let web = g.add_node(Asset::new("web", "Web Server", 5_000.0, 0.6));
+    let app = g.add_node(Asset::new("app", "App Server", 20_000.0, 0.4));
+    let db = g.add_node(Asset::new("db", "Database", 100_000.0, 0.3));
+    g.add_edge(web, app, 1.0);
+    g.add_edge(app, db, 1.0);
+    g
+}
+
+pub fn node_by_id(network: &NetworkGraph, id: &str) -> Option<NodeIndex> {
+    network.node_indices().find(|&n| network[n].id == id)
+}
+
+/// Self-loops, isolated (degree-0) nodes, duplicate asset ids, and overall
+/// connectedness found in a [`NetworkGraph`] by [`validate_network`].
+/// Reporting only: callers like
+/// [`crate::simulation::Simulator::try_new`] decide what, if anything, to
+/// reject based on it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkValidation {
+    /// Ids of assets with an edge from themselves to themselves.
+    pub self_loops: Vec<String>,
+    /// Ids of assets with neither an incoming nor an outgoing edge.
+    pub isolated_assets: Vec<String>,
+    /// Ids that appear on more than one asset, each listed once.
+    pub duplicate_ids: Vec<String>,
+    /// Whether the network forms a single connected component, treating
+    /// edges as undirected. Trivially `true` for a network with zero or one
+    /// node.
+    pub connected: bool,
+}
+
+impl NetworkValidation {
+    /// No self-loops, no isolated assets, no duplicate ids, and connected.
+    pub fn is_valid(&self) -> bool {
+        self.self_loops.is_empty() && self.isolated_assets.is_empty() && self.duplicate_ids.is_empty() && self.connected
+    }
+}
+
+/// Check `network` for the issues hand-built networks commonly hit; see
+/// [`NetworkValidation`].
+pub fn validate_network(network: &NetworkGraph) -> NetworkValidation {
+    let self_loops = network
+        .edge_indices()
+        .filter_map(|e| {
+            let (from, to) = network.edge_endpoints(e)?;
+            (from == to).then(|| network[from].id.clone())
+        })
+        .collect();
+
+    let isolated_assets = network
+        .node_indices()
+        .filter(|&n| {
+            network.neighbors_directed(n, Direction::Outgoing).next().is_none()
+                && network.neighbors_directed(n, Direction::Incoming).next().is_none()
+        })
+        .map(|n| network[n].id.clone())
+        .collect();
+
+    let mut seen_counts: HashMap<String, usize> = HashMap::new();
+    for node in network.node_indices() {
+        *seen_counts.entry(network[node].id.clone()).or_insert(0) += 1;
+    }
+    let mut duplicate_ids: Vec<String> = seen_counts.into_iter().filter(|(_, count)| *count > 1).map(|(id, _)| id).collect();
+    duplicate_ids.sort();
+
+    let connected = network.node_count() <= 1 || connected_components(network) == 1;
+
+    NetworkValidation { self_loops, isolated_assets, duplicate_ids, connected }
+}
+
+/// Errors from loading or saving a [`NetworkGraph`] as JSON.
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    /// An edge referenced an asset `id` that isn't in the file's asset list.
+    UnknownAsset(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "i/o error: {err}"),
+            NetworkError::Parse(err) => write!(f, "invalid network JSON: {err}"),
+            NetworkError::UnknownAsset(id) => write!(f, "edge references unknown asset '{id}'"),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetworkError::Io(err) => Some(err),
+            NetworkError::Parse(err) => Some(err),
+            NetworkError::UnknownAsset(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(err: io::Error) -> Self {
+        NetworkError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for NetworkError {
+    fn from(err: serde_json::Error) -> Self {
+        NetworkError::Parse(err)
+    }
+}
+
+/// On-disk shape for a [`NetworkGraph`]: a flat asset list plus
+/// `[from_id, to_id, weight]` edges referencing those assets by id.
+#[derive(Debug, Serialize, Deserialize)]
+struct NetworkFile {
+    assets: Vec<Asset>,
+    edges: Vec<(String, String, f64)>,
+}
+
+/// Load a [`NetworkGraph`] from a JSON file shaped like
+/// `{ "assets": [...], "edges": [[from_id, to_id, weight], ...] }`,
+/// validating that every edge references an asset that's actually present.
+pub fn load_network_json<P: AsRef<Path>>(path: P) -> Result<NetworkGraph, NetworkError> {
+    let contents = fs::read_to_string(path)?;
+    let file: NetworkFile = serde_json::from_str(&contents)?;
+
+    let mut network = NetworkGraph::new();
+    let mut index_by_id = HashMap::new();
+    for asset in file.assets {
+        let id = asset.id.clone();
+        let index = network.add_node(asset);
+        index_by_id.insert(id, index);
+    }
+    for (from, to, weight) in file.edges {
+        let from_index = *index_by_id.get(&from).ok_or_else(|| NetworkError::UnknownAsset(from.clone()))?;
+        let to_index = *index_by_id.get(&to).ok_or_else(|| NetworkError::UnknownAsset(to.clone()))?;
+        network.add_edge(from_index, to_index, weight);
+    }
+    Ok(network)
+}
+
+/// Serialize a [`NetworkGraph`] to the same JSON shape [`load_network_json`]
+/// reads, so a network can be round-tripped through disk.
+pub fn save_network_json<P: AsRef<Path>>(network: &NetworkGraph, path: P) -> Result<(), NetworkError> {
+    let assets: Vec<Asset> = network.node_indices().map(|n| network[n].clone()).collect();
+    let edges: Vec<(String, String, f64)> = network
+        .edge_indices()
+        .map(|e| {
+            let (from, to) = network.edge_endpoints(e).expect("edge index came from this graph");
+            (network[from].id.clone(), network[to].id.clone(), network[e])
+        })
+        .collect();
+
+    let json = serde_json::to_string_pretty(&NetworkFile { assets, edges })?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_round_trips_through_json() {
+        let network = create_example_network();
+        let path = std::env::temp_dir().join("secgame_network_round_trip_test.json");
+
+        save_network_json(&network, &path).unwrap();
+        let loaded = load_network_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.node_count(), network.node_count());
+        assert_eq!(loaded.edge_count(), network.edge_count());
+    }
+
+    #[test]
+    fn isolated_node_and_duplicate_id_are_both_flagged() {
+        let mut network = NetworkGraph::new();
+        let a = network.add_node(Asset::new("web", "Web Server", 5_000.0, 0.6));
+        let b = network.add_node(Asset::new("web", "Duplicate Web Server", 1_000.0, 0.2));
+        network.add_node(Asset::new("isolated", "Isolated", 500.0, 0.1));
+        network.add_edge(a, b, 1.0);
+
+        let validation = validate_network(&network);
+        assert_eq!(validation.duplicate_ids, vec!["web".to_string()]);
+        assert_eq!(validation.isolated_assets, vec!["isolated".to_string()]);
+        assert!(validation.self_loops.is_empty());
+        assert!(!validation.connected);
+        assert!(!validation.is_valid());
+    }
+}
@@ -0,0 +1,73 @@
+use ndarray::Array1;
+use serde::{Deserialize, Serialize};
+
+/// Running per-feature mean/variance, computed online via Welford's
+/// algorithm so it can be updated one state at a time without storing
+/// history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateNormalizer {
+    count: usize,
+    mean: Array1<f64>,
+    m2: Array1<f64>,
+}
+
+impl StateNormalizer {
+    pub fn new(feature_count: usize) -> Self {
+        StateNormalizer { count: 0, mean: Array1::zeros(feature_count), m2: Array1::zeros(feature_count) }
+    }
+
+    /// Fold one observed state into the running statistics.
+    pub fn observe(&mut self, state: &Array1<f64>) {
+        self.count += 1;
+        let delta = state - &self.mean;
+        self.mean = &self.mean + &delta / self.count as f64;
+        let delta2 = state - &self.mean;
+        self.m2 = &self.m2 + &delta * &delta2;
+    }
+
+    /// Per-feature variance from the observations seen so far (all zeros
+    /// before any observation).
+    pub fn variance(&self) -> Array1<f64> {
+        if self.count == 0 {
+            Array1::zeros(self.mean.len())
+        } else {
+            &self.m2 / self.count as f64
+        }
+    }
+
+    /// Scale `state` to roughly zero mean and unit variance using the
+    /// statistics observed so far. Returns `state` unchanged before any
+    /// observation, to avoid dividing by zero.
+    pub fn normalize(&self, state: &Array1<f64>) -> Array1<f64> {
+        if self.count == 0 {
+            return state.clone();
+        }
+        let std = self.variance().mapv(|v| v.sqrt().max(1e-8));
+        (state - &self.mean) / std
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settles_to_roughly_zero_mean_unit_variance() {
+        let mut normalizer = StateNormalizer::new(2);
+        let samples: Vec<Array1<f64>> = (0..200)
+            .map(|i| Array1::from_vec(vec![10.0 + (i % 7) as f64, 1_000.0 + (i % 13) as f64 * 5.0]))
+            .collect();
+        for state in &samples {
+            normalizer.observe(state);
+        }
+
+        let normalized: Vec<Array1<f64>> = samples.iter().map(|s| normalizer.normalize(s)).collect();
+        for feature in 0..2 {
+            let values: Vec<f64> = normalized.iter().map(|s| s[feature]).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            assert!(mean.abs() < 0.1, "feature {feature} mean {mean} not near zero");
+            assert!((variance - 1.0).abs() < 0.1, "feature {feature} variance {variance} not near one");
+        }
+    }
+}
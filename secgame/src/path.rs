@@ -0,0 +1,364 @@
+use std::fmt;
+
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::technique::{AccessLevel, AttackTechnique};
+
+/// A harsh, fixed penalty (as a multiple of target value) applied to a
+/// path's expected value once it touches a honeypot node.
+pub(crate) const HONEYPOT_PENALTY_FACTOR: f64 = 2.0;
+
+/// Near-certain detection probability assigned to a honeypot step,
+/// overriding whatever the technique itself would imply.
+pub(crate) const HONEYPOT_DETECTION_PROBABILITY: f64 = 0.95;
+
+/// The smallest `edge_weight` [`AttackPath::add_step`] will divide by.
+/// Network edge weights aren't validated to be positive before reaching
+/// here (a JSON-loaded network can carry a zero or negative weight), and
+/// dividing by zero (or by a negative weight paired with a `0.0`
+/// `success_rate`) produces a NaN that `f64::clamp` passes straight
+/// through, which later poisons any `partial_cmp(...).unwrap()` comparison
+/// over paths (e.g. [`crate::strategy::AttackStrategy::generate_optimal_path`]).
+const MIN_EDGE_WEIGHT: f64 = 1e-6;
+
+/// What an [`AttackStrategy`](crate::strategy::AttackStrategy) optimizes
+/// for when comparing candidate [`AttackPath`]s, via [`AttackPath::score`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum AttackObjective {
+    /// Maximize [`AttackPath::calculate_expected_value`] — the default, for
+    /// attackers after the highest-value target they can reasonably reach.
+    #[default]
+    MaximizeValue,
+    /// Minimize [`AttackPath::detection_probability`], ignoring target
+    /// value entirely — for stealth-first adversaries (e.g. espionage) who
+    /// would rather stay unseen at a lower-value target than risk detection
+    /// at a higher-value one.
+    MinimizeDetection,
+    /// A weighted blend of the two: `value_weight` in `[0, 1]` is how much
+    /// expected value counts versus stealth; `0.0` behaves like
+    /// [`AttackObjective::MinimizeDetection`] and `1.0` like
+    /// [`AttackObjective::MaximizeValue`].
+    Balanced { value_weight: f64 },
+}
+
+/// One hop of an [`AttackPath`]: a node visited with a chosen technique,
+/// scaled by the weight of the network edge traversed to reach it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackStep {
+    pub node: NodeIndex,
+    pub technique_id: String,
+    pub success_probability: f64,
+    pub detection_probability: f64,
+    /// Whether `node` is a deception target (a honeypot) rather than a
+    /// real asset.
+    pub honeypot: bool,
+    /// This hop's [`AttackTechnique::time_cost`] in hours, or `0.0` if the
+    /// technique doesn't record one.
+    pub time_cost: f64,
+}
+
+/// A concrete sequence of techniques an attacker uses to reach a target,
+/// along with the value of compromising that target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackPath {
+    pub steps: Vec<AttackStep>,
+    pub target_value: f64,
+}
+
+/// Errors from [`AttackPath::add_step`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepError {
+    /// The technique's `required_access` exceeds the attacker's current
+    /// access level, so the step couldn't actually be run.
+    InsufficientAccess { required: AccessLevel, current: AccessLevel },
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepError::InsufficientAccess { required, current } => {
+                write!(f, "technique requires {required:?} access but the attacker only has {current:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+impl AttackPath {
+    pub fn new(target_value: f64) -> Self {
+        AttackPath { steps: Vec::new(), target_value }
+    }
+
+    /// Add a hop to `node` using `technique`, scaling its base success and
+    /// detection probabilities by the traversed edge's `weight` (network
+    /// "distance": 1.0 is the default, undifferentiated link; higher is
+    /// harder/more monitored, lower is easier/less monitored) and by
+    /// `vulnerability` (the target's own `[0, 1]` hardening level: a
+    /// technique against a hardened target, `vulnerability` near `0.0`,
+    /// succeeds at only half its base rate, while a wide-open target,
+    /// `vulnerability` near `1.0`, succeeds at the full base rate). If
+    /// `node` is a honeypot, detection is forced to near-certain regardless
+    /// of the technique, edge weight, or vulnerability. Returns
+    /// [`StepError::InsufficientAccess`] instead of adding the step if
+    /// `technique.required_access` exceeds `access`, so a constructed path
+    /// can never include a technique the attacker couldn't actually run.
+    pub fn add_step(
+        &mut self,
+        node: NodeIndex,
+        technique: &AttackTechnique,
+        edge_weight: f64,
+        honeypot: bool,
+        vulnerability: f64,
+        access: AccessLevel,
+    ) -> Result<(), StepError> {
+        if technique.required_access > access {
+            return Err(StepError::InsufficientAccess { required: technique.required_access, current: access });
+        }
+
+        let detection_probability = if honeypot {
+            HONEYPOT_DETECTION_PROBABILITY
+        } else {
+            (technique.detectability * edge_weight).clamp(0.0, 1.0)
+        };
+        let vulnerability_factor = 0.5 + 0.5 * vulnerability;
+        let safe_edge_weight = if edge_weight > MIN_EDGE_WEIGHT { edge_weight } else { MIN_EDGE_WEIGHT };
+        self.steps.push(AttackStep {
+            node,
+            technique_id: technique.id.clone(),
+            success_probability: (technique.success_rate * vulnerability_factor / safe_edge_weight).clamp(0.0, 1.0),
+            detection_probability,
+            honeypot,
+            time_cost: technique.time_cost.unwrap_or(0.0),
+        });
+        Ok(())
+    }
+
+    /// Probability every step in the chain succeeds.
+    pub fn success_probability(&self) -> f64 {
+        self.steps.iter().map(|s| s.success_probability).product()
+    }
+
+    /// Probability the attacker is detected at some point along the path.
+    pub fn detection_probability(&self) -> f64 {
+        1.0 - self
+            .steps
+            .iter()
+            .map(|s| 1.0 - s.detection_probability)
+            .product::<f64>()
+    }
+
+    /// Expected value to the attacker: the target's value if they succeed
+    /// and evade detection, minus a penalty proportional to the chance of
+    /// being caught.
+    pub fn calculate_expected_value(&self) -> f64 {
+        let base = Self::expected_value_for(self.success_probability(), self.detection_probability(), self.target_value);
+        if self.steps.iter().any(|s| s.honeypot) {
+            base - self.target_value.abs().max(1.0) * HONEYPOT_PENALTY_FACTOR
+        } else {
+            base
+        }
+    }
+
+    /// Total dwell time (hours) to execute every step in this chain,
+    /// summing each step's [`AttackStep::time_cost`]. A standard
+    /// time-to-compromise metric, independent of whether the chain
+    /// ultimately succeeds or is detected.
+    pub fn calculate_time_to_compromise(&self) -> f64 {
+        self.steps.iter().map(|s| s.time_cost).sum()
+    }
+
+    /// This path's score under `objective`, higher is better. Used by
+    /// [`crate::strategy::AttackStrategy::generate_optimal_path`] to pick
+    /// among candidate paths.
+    pub fn score(&self, objective: AttackObjective) -> f64 {
+        let stealth_score = -self.detection_probability() * self.target_value.abs().max(1.0);
+        match objective {
+            AttackObjective::MaximizeValue => self.calculate_expected_value(),
+            AttackObjective::MinimizeDetection => stealth_score,
+            AttackObjective::Balanced { value_weight } => {
+                value_weight * self.calculate_expected_value() + (1.0 - value_weight) * stealth_score
+            }
+        }
+    }
+
+    fn expected_value_for(success: f64, detection: f64, target_value: f64) -> f64 {
+        success * target_value * (1.0 - detection) - detection * target_value * 0.5
+    }
+
+    /// Sweep the target value over `[0.5 * base, 2.0 * base]` and report the
+    /// resulting expected value at each point, holding success/detection
+    /// probabilities fixed. Useful for seeing how much defense investment
+    /// matters relative to asset value.
+    pub fn sensitivity(&self, base_target_value: f64) -> Vec<(f64, f64)> {
+        let success = self.success_probability();
+        let detection = self.detection_probability();
+        let steps = 10;
+        (0..=steps)
+            .map(|i| {
+                let factor = 0.5 + (1.5 * i as f64) / steps as f64;
+                let value = base_target_value * factor;
+                (value, Self::expected_value_for(success, detection, value))
+            })
+            .collect()
+    }
+
+    /// The defense effectiveness (in `[0, 1]`, added on top of the path's
+    /// baseline detection probability) at which expected value crosses
+    /// zero, i.e. the point past which this attack stops being profitable.
+    pub fn break_even_defense_effectiveness(&self) -> f64 {
+        let success = self.success_probability();
+        let baseline_detection = self.detection_probability();
+        let target_value = self.target_value;
+
+        let ev_at = |effectiveness: f64| {
+            let effective_detection = baseline_detection + effectiveness * (1.0 - baseline_detection);
+            Self::expected_value_for(success, effective_detection, target_value)
+        };
+
+        if ev_at(0.0) <= 0.0 {
+            return 0.0;
+        }
+        if ev_at(1.0) > 0.0 {
+            return 1.0;
+        }
+
+        let (mut lo, mut hi) = (0.0_f64, 1.0_f64);
+        for _ in 0..50 {
+            let mid = (lo + hi) / 2.0;
+            if ev_at(mid) > 0.0 {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        (lo + hi) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::technique::{AccessLevel, AttackPhase, AttackTechnique};
+    use petgraph::graph::NodeIndex;
+
+    #[test]
+    fn break_even_effectiveness_is_in_unit_range() {
+        let technique = AttackTechnique::new(
+            "t",
+            "Test Technique",
+            AttackPhase::Execution,
+            0.7,
+            0.2,
+            AccessLevel::None,
+            100.0,
+        );
+        let mut path = AttackPath::new(10_000.0);
+        path.add_step(NodeIndex::new(0), &technique, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        let break_even = path.break_even_defense_effectiveness();
+        assert!((0.0..=1.0).contains(&break_even));
+    }
+
+    #[test]
+    fn honeypot_step_sharply_reduces_expected_value() {
+        let technique = AttackTechnique::new(
+            "t",
+            "Test Technique",
+            AttackPhase::Execution,
+            0.9,
+            0.1,
+            AccessLevel::None,
+            100.0,
+        );
+
+        let mut clean_path = AttackPath::new(10_000.0);
+        clean_path.add_step(NodeIndex::new(0), &technique, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        let mut honeypot_path = AttackPath::new(10_000.0);
+        honeypot_path.add_step(NodeIndex::new(0), &technique, 1.0, true, 1.0, AccessLevel::None).unwrap();
+
+        assert!(honeypot_path.calculate_expected_value() < clean_path.calculate_expected_value() - 10_000.0);
+    }
+
+    #[test]
+    fn longer_technique_chain_yields_a_larger_time_to_compromise() {
+        let phishing = AttackTechnique::new("phishing", "Phishing", AttackPhase::InitialAccess, 0.4, 0.2, AccessLevel::None, 100.0)
+            .with_time_cost(2.0);
+        let sqli =
+            AttackTechnique::new("sqli", "SQL Injection", AttackPhase::Execution, 0.6, 0.3, AccessLevel::None, 300.0).with_time_cost(4.0);
+
+        let mut short_path = AttackPath::new(10_000.0);
+        short_path.add_step(NodeIndex::new(0), &phishing, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        let mut long_path = AttackPath::new(10_000.0);
+        long_path.add_step(NodeIndex::new(0), &phishing, 1.0, false, 1.0, AccessLevel::None).unwrap();
+        long_path.add_step(NodeIndex::new(1), &sqli, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        assert_eq!(short_path.calculate_time_to_compromise(), 2.0);
+        assert_eq!(long_path.calculate_time_to_compromise(), 6.0);
+        assert!(long_path.calculate_time_to_compromise() > short_path.calculate_time_to_compromise());
+    }
+
+    #[test]
+    fn lowering_vulnerability_reduces_path_success_probability() {
+        let technique = AttackTechnique::new(
+            "t",
+            "Test Technique",
+            AttackPhase::Execution,
+            0.8,
+            0.1,
+            AccessLevel::None,
+            100.0,
+        );
+
+        let mut hardened_path = AttackPath::new(10_000.0);
+        hardened_path.add_step(NodeIndex::new(0), &technique, 1.0, false, 0.0, AccessLevel::None).unwrap();
+
+        let mut wide_open_path = AttackPath::new(10_000.0);
+        wide_open_path.add_step(NodeIndex::new(0), &technique, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        assert!(hardened_path.success_probability() < wide_open_path.success_probability());
+    }
+
+    #[test]
+    fn minimize_detection_objective_picks_the_lower_detection_path_switching_from_maximize_value() {
+        // A noisy technique against a high-value target beats a quiet one
+        // against a lower-value target on expected value alone, but the
+        // quiet path is far less likely to get the attacker caught.
+        let noisy = AttackTechnique::new("noisy", "Noisy", AttackPhase::Execution, 0.95, 0.5, AccessLevel::None, 100.0);
+        let mut high_value_path = AttackPath::new(100_000.0);
+        high_value_path.add_step(NodeIndex::new(0), &noisy, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        let quiet = AttackTechnique::new("quiet", "Quiet", AttackPhase::Execution, 0.3, 0.05, AccessLevel::None, 100.0);
+        let mut stealthy_path = AttackPath::new(10_000.0);
+        stealthy_path.add_step(NodeIndex::new(1), &quiet, 1.0, false, 1.0, AccessLevel::None).unwrap();
+
+        let paths = [high_value_path, stealthy_path];
+        let by_value = paths
+            .iter()
+            .max_by(|a, b| a.score(AttackObjective::MaximizeValue).partial_cmp(&b.score(AttackObjective::MaximizeValue)).unwrap())
+            .unwrap();
+        let by_stealth = paths
+            .iter()
+            .max_by(|a, b| {
+                a.score(AttackObjective::MinimizeDetection).partial_cmp(&b.score(AttackObjective::MinimizeDetection)).unwrap()
+            })
+            .unwrap();
+
+        assert!((by_value.target_value - 100_000.0).abs() < f64::EPSILON);
+        assert!((by_stealth.target_value - 10_000.0).abs() < f64::EPSILON);
+        assert!(by_stealth.detection_probability() < by_value.detection_probability());
+    }
+
+    #[test]
+    fn adding_an_admin_required_technique_at_user_access_is_rejected() {
+        let technique = AttackTechnique::new("privesc", "Privilege Escalation", AttackPhase::PrivilegeEscalation, 0.5, 0.2, AccessLevel::Admin, 100.0);
+        let mut path = AttackPath::new(10_000.0);
+
+        let err = path.add_step(NodeIndex::new(0), &technique, 1.0, false, 1.0, AccessLevel::User).unwrap_err();
+        assert_eq!(err, StepError::InsufficientAccess { required: AccessLevel::Admin, current: AccessLevel::User });
+        assert!(path.steps.is_empty());
+    }
+}
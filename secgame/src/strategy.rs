@@ -0,0 +1,429 @@
+use std::collections::HashSet;
+
+use petgraph::algo::{all_simple_paths, astar};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{Bfs, NodeFiltered};
+
+use crate::defense::AttackerProfile;
+use crate::network::NetworkGraph;
+use crate::path::{AttackObjective, AttackPath};
+use crate::technique::{AccessLevel, AttackPhase, AttackTechnique};
+
+/// Chooses techniques and builds attack paths through a [`NetworkGraph`].
+pub struct AttackStrategy<'a> {
+    network: &'a NetworkGraph,
+    techniques: &'a [AttackTechnique],
+    profile: Option<AttackerProfile>,
+    objective: AttackObjective,
+    max_path_length: Option<usize>,
+}
+
+impl<'a> AttackStrategy<'a> {
+    pub fn new(network: &'a NetworkGraph, techniques: &'a [AttackTechnique]) -> Self {
+        AttackStrategy { network, techniques, profile: None, objective: AttackObjective::default(), max_path_length: None }
+    }
+
+    /// Build a strategy for an attacker with a given skill and resource
+    /// budget: technique success rates are scaled by `profile.skill_level`,
+    /// and techniques whose `resource_cost` exceeds `profile.resources`
+    /// are treated as unavailable.
+    pub fn with_profile(network: &'a NetworkGraph, techniques: &'a [AttackTechnique], profile: AttackerProfile) -> Self {
+        AttackStrategy { network, techniques, profile: Some(profile), objective: AttackObjective::default(), max_path_length: None }
+    }
+
+    /// Change what [`AttackStrategy::generate_optimal_path`] optimizes for
+    /// (defaults to [`AttackObjective::MaximizeValue`]).
+    pub fn with_objective(mut self, objective: AttackObjective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Cap [`AttackStrategy::find_path_to_target`] (and anything built on
+    /// it, e.g. [`AttackStrategy::generate_optimal_path`]) at `max_hops`
+    /// hops, aborting and returning `None` instead of returning a
+    /// pathologically long chain. Unbounded by default.
+    pub fn with_max_path_length(mut self, max_hops: usize) -> Self {
+        self.max_path_length = Some(max_hops);
+        self
+    }
+
+    fn affordable(&self, technique: &AttackTechnique) -> bool {
+        self.profile.as_ref().map(|p| technique.resource_cost <= p.resources).unwrap_or(true)
+    }
+
+    /// `technique.success_rate` scaled by the attacker's skill, or
+    /// unscaled if this strategy has no profile.
+    fn effective_success_rate(&self, technique: &AttackTechnique) -> f64 {
+        match &self.profile {
+            Some(profile) => (technique.success_rate * profile.skill_level).clamp(0.0, 1.0),
+            None => technique.success_rate,
+        }
+    }
+
+    /// The highest-effective-success-rate technique available for `phase`
+    /// at the attacker's current `access` level, among techniques they can
+    /// afford. Techniques with a NaN `success_rate` are skipped, and ties
+    /// are broken by `id` so the choice is deterministic and reproducible
+    /// across runs.
+    pub fn select_technique(&self, phase: AttackPhase, access: AccessLevel) -> Option<&'a AttackTechnique> {
+        self.techniques
+            .iter()
+            .filter(|t| t.phase == phase && t.required_access <= access && !t.success_rate.is_nan() && self.affordable(t))
+            .fold(None, |best: Option<&'a AttackTechnique>, t| match best {
+                None => Some(t),
+                Some(b) => {
+                    let (t_rate, b_rate) = (self.effective_success_rate(t), self.effective_success_rate(b));
+                    if t_rate > b_rate || (t_rate == b_rate && t.id < b.id) {
+                        Some(t)
+                    } else {
+                        Some(b)
+                    }
+                }
+            })
+    }
+
+    /// Shortest-hop route from `entry` to `target`, built into an
+    /// [`AttackPath`] by applying one technique per hop. Each step's
+    /// success rate reflects the attacker's skill, if a profile is set.
+    /// Offline assets are excluded, both as hops along the route and as
+    /// `entry`/`target` themselves. If `entry` is already
+    /// [`Asset::compromised`](crate::network::Asset), the path starts with
+    /// elevated access instead of [`AccessLevel::None`]. Returns `None`
+    /// instead of a path longer than [`AttackStrategy::with_max_path_length`]'s
+    /// bound, if one is set.
+    pub fn find_path_to_target(&self, entry: NodeIndex, target: NodeIndex) -> Option<AttackPath> {
+        if self.network[entry].offline || self.network[target].offline {
+            return None;
+        }
+        let online = NodeFiltered::from_fn(self.network, |n| !self.network[n].offline);
+        let (_, node_path) = astar(&online, entry, |n| n == target, |e| *e.weight(), |_| 0.0)?;
+        if let Some(max_hops) = self.max_path_length {
+            if node_path.len().saturating_sub(1) > max_hops {
+                return None;
+            }
+        }
+        self.build_path(&node_path)
+    }
+
+    /// Build an [`AttackPath`] by applying one technique per hop along
+    /// `node_path` (entry first, target last). The entry's access starts
+    /// elevated to [`AccessLevel::User`] rather than [`AccessLevel::None`]
+    /// if it's already compromised, modeling a breach-assumption scenario
+    /// where the attacker begins with a foothold instead of none at all.
+    fn build_path(&self, node_path: &[NodeIndex]) -> Option<AttackPath> {
+        let &target = node_path.last()?;
+        let mut path = AttackPath::new(self.network[target].value);
+        let mut access = if self.network[node_path[0]].compromised { AccessLevel::User } else { AccessLevel::None };
+        let phases = [
+            AttackPhase::InitialAccess,
+            AttackPhase::Execution,
+            AttackPhase::PrivilegeEscalation,
+            AttackPhase::LateralMovement,
+            AttackPhase::Exfiltration,
+        ];
+        for (i, &node) in node_path.iter().enumerate().skip(1) {
+            let phase = phases[i.min(phases.len() - 1)];
+            let technique = self.select_technique(phase, access)?;
+            let mut adjusted = technique.clone();
+            adjusted.success_rate = self.effective_success_rate(technique);
+            let prev = node_path[i - 1];
+            let edge_weight = self.network.find_edge(prev, node).map(|e| self.network[e]).unwrap_or(1.0);
+            path.add_step(node, &adjusted, edge_weight, false, self.network[node].vulnerability, access).ok()?;
+            access = AccessLevel::User;
+        }
+        Some(path)
+    }
+
+    /// Exhaustively enumerate every simple path (capped at
+    /// `MAX_INTERMEDIATE_NODES` intermediate hops, to keep this tractable)
+    /// from any exposed entry node to `target`, and return the one that
+    /// scores best under this strategy's [`AttackObjective`]. Ground truth
+    /// for checking how much [`AttackStrategy::generate_optimal_path`]'s
+    /// shortest-hop heuristic loses on small graphs; exponential in path
+    /// count, so not meant for production-sized networks.
+    pub fn optimal_path_bruteforce(&self, target: NodeIndex) -> Option<AttackPath> {
+        const MAX_INTERMEDIATE_NODES: usize = 6;
+        if self.network[target].offline {
+            return None;
+        }
+        self.network
+            .node_indices()
+            .filter(|&n| n != target && !self.network[n].offline && self.network[n].exposure > 0.0)
+            .flat_map(|entry| {
+                all_simple_paths::<Vec<NodeIndex>, _>(self.network, entry, target, 0, Some(MAX_INTERMEDIATE_NODES))
+            })
+            .filter(|node_path| node_path.iter().all(|&n| !self.network[n].offline))
+            .filter_map(|node_path| self.build_path(&node_path))
+            .max_by(|a, b| a.score(self.objective).partial_cmp(&b.score(self.objective)).unwrap())
+    }
+
+    /// Every node reachable from `entry` by following edges forward,
+    /// including `entry` itself. Offline assets are skipped, whether as
+    /// `entry` (an empty set) or as intermediate hops.
+    pub fn reachable_targets(&self, entry: NodeIndex) -> HashSet<NodeIndex> {
+        let mut reachable = HashSet::new();
+        if self.network[entry].offline {
+            return reachable;
+        }
+        let online = NodeFiltered::from_fn(self.network, |n| !self.network[n].offline);
+        let mut bfs = Bfs::new(&online, entry);
+        while let Some(node) = bfs.next(&online) {
+            reachable.insert(node);
+        }
+        reachable
+    }
+
+    /// Try every exposed node as an entry point and keep the path that
+    /// scores best under this strategy's [`AttackObjective`] (defaults to
+    /// maximizing expected value). Offline assets are never considered as
+    /// entry points, since [`AttackStrategy::find_path_to_target`] would
+    /// reject them anyway, and nor are assets with zero
+    /// [`Asset::exposure`](crate::network::Asset) — only plausible
+    /// internet-facing footholds are tried.
+    pub fn generate_optimal_path(&self, target: NodeIndex) -> Option<AttackPath> {
+        self.network
+            .node_indices()
+            .filter(|&n| n != target && !self.network[n].offline && self.network[n].exposure > 0.0)
+            .filter_map(|entry| self.find_path_to_target(entry, target))
+            .max_by(|a, b| a.score(self.objective).partial_cmp(&b.score(self.objective)).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{create_example_network, NetworkGraph};
+    use crate::technique::example_techniques;
+
+    #[test]
+    fn equal_score_techniques_break_ties_by_id() {
+        let network = create_example_network();
+        let techniques = vec![
+            AttackTechnique::new("zeta", "Zeta", AttackPhase::InitialAccess, 0.5, 0.1, AccessLevel::None, 100.0),
+            AttackTechnique::new("alpha", "Alpha", AttackPhase::InitialAccess, 0.5, 0.1, AccessLevel::None, 100.0),
+        ];
+        let strategy = AttackStrategy::new(&network, &techniques);
+
+        let chosen = strategy.select_technique(AttackPhase::InitialAccess, AccessLevel::None).unwrap();
+        assert_eq!(chosen.id, "alpha");
+    }
+
+    #[test]
+    fn heavier_edge_makes_lighter_alternative_path_preferred() {
+        let mut network = NetworkGraph::new();
+        let entry = network.add_node(crate::network::Asset::new("entry", "Entry", 0.0, 0.5));
+        let light_hop = network.add_node(crate::network::Asset::new("light", "Light Hop", 0.0, 0.5));
+        let heavy_hop = network.add_node(crate::network::Asset::new("heavy", "Heavy Hop", 0.0, 0.5));
+        let target = network.add_node(crate::network::Asset::new("target", "Target", 100_000.0, 0.5));
+        network.add_edge(entry, light_hop, 1.0);
+        network.add_edge(light_hop, target, 1.0);
+        network.add_edge(entry, heavy_hop, 5.0);
+        network.add_edge(heavy_hop, target, 5.0);
+
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(&network, &techniques);
+
+        let path = strategy.find_path_to_target(entry, target).unwrap();
+        assert!(path.steps.iter().any(|s| s.node == light_hop));
+        assert!(!path.steps.iter().any(|s| s.node == heavy_hop));
+    }
+
+    #[test]
+    fn zero_weight_edge_does_not_panic_generate_optimal_path_with_a_nan_score() {
+        let mut network = NetworkGraph::new();
+        let entry = network.add_node(crate::network::Asset::new("entry", "Entry", 0.0, 0.5));
+        let target = network.add_node(crate::network::Asset::new("target", "Target", 100_000.0, 0.5));
+        network.add_edge(entry, target, 0.0);
+
+        // success_rate 0.0 combined with the 0.0 edge weight above would
+        // divide 0.0 by 0.0 in AttackPath::add_step without its
+        // MIN_EDGE_WEIGHT floor, producing a NaN score that poisons the
+        // `max_by`/`partial_cmp(...).unwrap()` below.
+        let techniques =
+            vec![AttackTechnique::new("exec", "Execution", AttackPhase::Execution, 0.0, 0.1, AccessLevel::None, 10.0)];
+        let strategy = AttackStrategy::new(&network, &techniques);
+
+        let path = strategy.generate_optimal_path(target).unwrap();
+        assert!((0.0..=1.0).contains(&path.success_probability()));
+
+        let path = strategy.optimal_path_bruteforce(target).unwrap();
+        assert!((0.0..=1.0).contains(&path.success_probability()));
+    }
+
+    #[test]
+    fn high_skill_profile_yields_higher_path_success_probability() {
+        let network = create_example_network();
+        let techniques = example_techniques();
+        let web = crate::network::node_by_id(&network, "web").unwrap();
+        let db = crate::network::node_by_id(&network, "db").unwrap();
+
+        let low_skill = AttackStrategy::with_profile(
+            &network,
+            &techniques,
+            AttackerProfile::new(0.3, 1_000.0, vec![]),
+        );
+        let high_skill = AttackStrategy::with_profile(
+            &network,
+            &techniques,
+            AttackerProfile::new(1.0, 1_000.0, vec![]),
+        );
+
+        let low_path = low_skill.find_path_to_target(web, db).unwrap();
+        let high_path = high_skill.find_path_to_target(web, db).unwrap();
+        assert!(high_path.success_probability() > low_path.success_probability());
+    }
+
+    #[test]
+    fn disconnected_node_is_excluded_from_reachable_targets() {
+        let mut network = NetworkGraph::new();
+        let entry = network.add_node(crate::network::Asset::new("entry", "Entry", 0.0, 0.5));
+        let connected = network.add_node(crate::network::Asset::new("connected", "Connected", 0.0, 0.5));
+        let isolated = network.add_node(crate::network::Asset::new("isolated", "Isolated", 0.0, 0.5));
+        network.add_edge(entry, connected, 1.0);
+
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(&network, &techniques);
+        let reachable = strategy.reachable_targets(entry);
+
+        assert!(reachable.contains(&entry));
+        assert!(reachable.contains(&connected));
+        assert!(!reachable.contains(&isolated));
+    }
+
+    #[test]
+    fn reversing_an_edge_makes_a_previously_reachable_target_unreachable() {
+        let mut network = NetworkGraph::new();
+        let entry = network.add_node(crate::network::Asset::new("entry", "Entry", 0.0, 0.5));
+        let target = network.add_node(crate::network::Asset::new("target", "Target", 0.0, 0.5));
+        let edge = network.add_edge(entry, target, 1.0);
+
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(&network, &techniques);
+        assert!(strategy.reachable_targets(entry).contains(&target));
+
+        network.remove_edge(edge);
+        network.add_edge(target, entry, 1.0);
+        let strategy = AttackStrategy::new(&network, &techniques);
+        assert!(!strategy.reachable_targets(entry).contains(&target));
+    }
+
+    #[test]
+    fn heuristic_optimal_path_is_within_tolerance_of_brute_force_optimum() {
+        let network = create_example_network();
+        let techniques = example_techniques();
+        let db = crate::network::node_by_id(&network, "db").unwrap();
+        let strategy = AttackStrategy::new(&network, &techniques);
+
+        let heuristic = strategy.generate_optimal_path(db).unwrap();
+        let brute_force = strategy.optimal_path_bruteforce(db).unwrap();
+
+        let heuristic_value = heuristic.score(AttackObjective::MaximizeValue);
+        let optimal_value = brute_force.score(AttackObjective::MaximizeValue);
+        assert!(heuristic_value <= optimal_value + 1e-9, "heuristic ({heuristic_value}) beat brute force ({optimal_value})");
+        assert!((optimal_value - heuristic_value).abs() / optimal_value.abs().max(1.0) < 0.05);
+    }
+
+    #[test]
+    fn only_the_exposed_web_server_is_used_as_an_entry_point() {
+        let mut network = create_example_network();
+        let web = crate::network::node_by_id(&network, "web").unwrap();
+        let app = crate::network::node_by_id(&network, "app").unwrap();
+        let db = crate::network::node_by_id(&network, "db").unwrap();
+        network[app].exposure = 0.0;
+        network[db].exposure = 0.0;
+
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(&network, &techniques);
+        let only_path_from_web = strategy.find_path_to_target(web, db).unwrap();
+
+        // With `app` and `db` unexposed, `web` is the only node entries can
+        // be drawn from, so both pathfinders must produce the same path
+        // `find_path_to_target` builds from `web` (two hops: web -> app,
+        // app -> db), never the shorter one-hop path an entry of `app`
+        // would give.
+        let heuristic = strategy.generate_optimal_path(db).unwrap();
+        assert_eq!(heuristic.steps.len(), only_path_from_web.steps.len());
+        assert_eq!(heuristic.steps.last().map(|s| s.node), Some(db));
+
+        let brute_force = strategy.optimal_path_bruteforce(db).unwrap();
+        assert_eq!(brute_force.steps.len(), only_path_from_web.steps.len());
+    }
+
+    #[test]
+    fn compromised_entry_yields_higher_path_success_probability_than_external_entry() {
+        let network = create_example_network();
+        let techniques = example_techniques();
+        let web = crate::network::node_by_id(&network, "web").unwrap();
+        let app = crate::network::node_by_id(&network, "app").unwrap();
+        let db = crate::network::node_by_id(&network, "db").unwrap();
+
+        let strategy = AttackStrategy::new(&network, &techniques);
+        let external_path = strategy.find_path_to_target(web, db).unwrap();
+
+        let mut breached = network.clone();
+        breached[app].compromised = true;
+        let breached_strategy = AttackStrategy::new(&breached, &techniques);
+        let breached_path = breached_strategy.find_path_to_target(app, db).unwrap();
+
+        assert!(breached_path.success_probability() > external_path.success_probability());
+    }
+
+    #[test]
+    fn offline_node_is_excluded_from_pathfinding_and_reachable_targets() {
+        let mut network = create_example_network();
+        let web = crate::network::node_by_id(&network, "web").unwrap();
+        let app = crate::network::node_by_id(&network, "app").unwrap();
+        let db = crate::network::node_by_id(&network, "db").unwrap();
+        network[app].offline = true;
+
+        let techniques = example_techniques();
+        let strategy = AttackStrategy::new(&network, &techniques);
+
+        assert!(strategy.find_path_to_target(web, db).is_none());
+        assert!(!strategy.reachable_targets(web).contains(&app));
+        assert!(!strategy.reachable_targets(web).contains(&db));
+    }
+
+    #[test]
+    fn max_path_length_bounds_how_far_find_path_to_target_will_chain_hops() {
+        let mut network = NetworkGraph::new();
+        let mut nodes = Vec::new();
+        for i in 0..20 {
+            nodes.push(network.add_node(crate::network::Asset::new(format!("n{i}"), format!("N{i}"), 0.0, 0.5)));
+        }
+        for (&from, &to) in nodes.iter().zip(nodes.iter().skip(1)) {
+            network.add_edge(from, to, 1.0);
+        }
+        let entry = nodes[0];
+        let far_target = nodes[19];
+
+        // A technique for every phase `build_path` cycles through, so a
+        // 19-hop chain actually builds instead of stalling on a phase with
+        // no available technique.
+        let techniques = vec![
+            AttackTechnique::new("initial", "Initial Access", AttackPhase::InitialAccess, 0.9, 0.1, AccessLevel::None, 0.0),
+            AttackTechnique::new("exec", "Execution", AttackPhase::Execution, 0.9, 0.1, AccessLevel::None, 0.0),
+            AttackTechnique::new("privesc", "Privilege Escalation", AttackPhase::PrivilegeEscalation, 0.9, 0.1, AccessLevel::None, 0.0),
+            AttackTechnique::new("lateral", "Lateral Movement", AttackPhase::LateralMovement, 0.9, 0.1, AccessLevel::None, 0.0),
+            AttackTechnique::new("exfil", "Exfiltration", AttackPhase::Exfiltration, 0.9, 0.1, AccessLevel::None, 0.0),
+        ];
+        let short_leash = AttackStrategy::new(&network, &techniques).with_max_path_length(5);
+        assert!(short_leash.find_path_to_target(entry, far_target).is_none());
+
+        let long_leash = AttackStrategy::new(&network, &techniques).with_max_path_length(19);
+        assert!(long_leash.find_path_to_target(entry, far_target).is_some());
+    }
+
+    #[test]
+    fn nan_success_rate_is_skipped_without_panicking() {
+        let network = create_example_network();
+        let techniques = vec![
+            AttackTechnique::new("broken", "Broken", AttackPhase::InitialAccess, f64::NAN, 0.1, AccessLevel::None, 100.0),
+            AttackTechnique::new("ok", "Ok", AttackPhase::InitialAccess, 0.3, 0.1, AccessLevel::None, 100.0),
+        ];
+        let strategy = AttackStrategy::new(&network, &techniques);
+
+        let chosen = strategy.select_technique(AttackPhase::InitialAccess, AccessLevel::None).unwrap();
+        assert_eq!(chosen.id, "ok");
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+/// MITRE-ATT&CK-flavored attack phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AttackPhase {
+    Reconnaissance,
+    InitialAccess,
+    Execution,
+    PrivilegeEscalation,
+    LateralMovement,
+    Exfiltration,
+}
+
+/// How much access an attacker has on the current node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AccessLevel {
+    None,
+    User,
+    Admin,
+}
+
+/// A single capability an attacker can attempt, e.g. "phishing" or
+/// "SQL injection".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttackTechnique {
+    pub id: String,
+    pub name: String,
+    pub phase: AttackPhase,
+    /// Intrinsic probability of success, independent of the target.
+    pub success_rate: f64,
+    /// Probability a defender notices this technique being used.
+    pub detectability: f64,
+    pub required_access: AccessLevel,
+    /// Rough resource cost (tooling, infrastructure, time) to use this
+    /// technique, compared against an [`crate::defense::AttackerProfile`]'s
+    /// `resources`.
+    pub resource_cost: f64,
+    /// Hours this technique typically takes to execute, if known. Feeds
+    /// [`crate::path::AttackPath::calculate_time_to_compromise`]; `None`
+    /// is treated as instantaneous (`0.0` hours) rather than unknown, so
+    /// techniques that predate this field don't skew a chain's total.
+    #[serde(default)]
+    pub time_cost: Option<f64>,
+}
+
+impl AttackTechnique {
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        phase: AttackPhase,
+        success_rate: f64,
+        detectability: f64,
+        required_access: AccessLevel,
+        resource_cost: f64,
+    ) -> Self {
+        AttackTechnique {
+            id: id.into(),
+            name: name.into(),
+            phase,
+            success_rate,
+            detectability,
+            required_access,
+            resource_cost,
+            time_cost: None,
+        }
+    }
+
+    /// Override this technique's default unknown (treated as
+    /// instantaneous) time cost.
+    pub fn with_time_cost(mut self, time_cost: f64) -> Self {
+        self.time_cost = Some(time_cost);
+        self
+    }
+}
+
+/// A small built-in technique catalog used by examples and tests.
+pub fn example_techniques() -> Vec<AttackTechnique> {
+    vec![
+        AttackTechnique::new(
+            "phishing",
+            "Spear Phishing",
+            AttackPhase::InitialAccess,
+            0.4,
+            0.2,
+            AccessLevel::None,
+            100.0,
+        ),
+        AttackTechnique::new(
+            "sqli",
+            "SQL Injection",
+            AttackPhase::Execution,
+            0.6,
+            0.3,
+            AccessLevel::None,
+            300.0,
+        ),
+        AttackTechnique::new(
+            "privesc",
+            "Local Privilege Escalation",
+            AttackPhase::PrivilegeEscalation,
+            0.5,
+            0.4,
+            AccessLevel::User,
+            500.0,
+        ),
+        AttackTechnique::new(
+            "exfil",
+            "Data Exfiltration",
+            AttackPhase::Exfiltration,
+            0.8,
+            0.5,
+            AccessLevel::User,
+            800.0,
+        ),
+    ]
+}
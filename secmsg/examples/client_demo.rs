@@ -0,0 +1,50 @@
+//! Drives the X3DH handshake and double-ratchet session directly, without
+//! the HTTP server, to demonstrate the crypto in isolation.
+
+use chrono::Utc;
+use ed25519_dalek::Signer;
+use secmsg::{x3dh_initiate, x3dh_respond, IdentityKeyPair, PreKeyBundle, RatchetState};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fn main() {
+    let alice = IdentityKeyPair::generate();
+    let bob = IdentityKeyPair::generate();
+
+    let bob_signed_prekey_secret = StaticSecret::random_from_rng(rand::rngs::OsRng);
+    let bob_signed_prekey = PublicKey::from(&bob_signed_prekey_secret);
+    let bob_signed_prekey_signature = bob.signing_key.sign(bob_signed_prekey.as_bytes());
+
+    let now = Utc::now();
+    let bob_bundle = PreKeyBundle {
+        identity_key: bob.verifying_key(),
+        identity_dh: bob.dh_public(),
+        signed_prekey: bob_signed_prekey,
+        signed_prekey_signature: bob_signed_prekey_signature,
+        one_time_prekey: None,
+        created_at: now,
+        signed_prekey_expiry: now + chrono::Duration::days(7),
+    };
+
+    let initiation = x3dh_initiate(&alice, &bob_bundle, now).expect("bob's bundle should verify");
+    let bob_root = x3dh_respond(
+        &bob,
+        &bob_signed_prekey_secret,
+        None,
+        &alice.dh_public(),
+        &initiation.ephemeral_public,
+        initiation.used_one_time_prekey,
+    )
+    .expect("alice and bob must agree on whether a one-time prekey was used");
+    assert_eq!(initiation.root_key, bob_root, "both sides must agree on the X3DH root key");
+
+    let mut alice_ratchet = RatchetState::new_initiator(initiation.root_key);
+    let mut bob_ratchet = RatchetState::new_responder(bob_root);
+
+    let outgoing = alice_ratchet.encrypt(b"hey bob, it's alice", &alice.signing_key).expect("alice's counter has room to encrypt");
+    let plaintext = bob_ratchet.decrypt(&outgoing, &alice.verifying_key()).expect("bob should be able to decrypt alice's message");
+    println!("bob received: {}", String::from_utf8_lossy(&plaintext));
+
+    let reply = bob_ratchet.encrypt(b"hi alice, loud and clear", &bob.signing_key).expect("bob's counter has room to encrypt");
+    let plaintext = alice_ratchet.decrypt(&reply, &bob.verifying_key()).expect("alice should be able to decrypt bob's reply");
+    println!("alice received: {}", String::from_utf8_lossy(&plaintext));
+}
@@ -0,0 +1,90 @@
+//! Password hashing and session token generation.
+
+use std::fmt;
+
+use argon2::password_hash::rand_core::OsRng as ArgonOsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+/// Errors from hashing or verifying a password.
+#[derive(Debug)]
+pub struct AuthError(String);
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "password hashing error: {}", self.0)
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Argon2 cost parameters for [`hash_password_with_config`]. The stored
+/// hash string embeds whichever parameters were used, so [`verify_password`]
+/// needs no matching configuration -- it reads them back out of the hash.
+#[derive(Debug, Clone, Copy)]
+pub struct Argon2Config {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Argon2Config { memory_kib: Params::DEFAULT_M_COST, iterations: Params::DEFAULT_T_COST, parallelism: Params::DEFAULT_P_COST }
+    }
+}
+
+/// Hash `password` with Argon2's default parameters, returning the
+/// self-describing hash string (salt and parameters included) that's safe
+/// to store directly.
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    hash_password_with_config(password, Argon2Config::default())
+}
+
+/// Like [`hash_password`], but with explicit cost parameters.
+pub fn hash_password_with_config(password: &str, config: Argon2Config) -> Result<String, AuthError> {
+    let params = Params::new(config.memory_kib, config.iterations, config.parallelism, None).map_err(|err| AuthError(err.to_string()))?;
+    let argon2 = Argon2::new(Algorithm::default(), Version::default(), params);
+    let salt = SaltString::generate(&mut ArgonOsRng);
+    argon2.hash_password(password.as_bytes(), &salt).map(|hash| hash.to_string()).map_err(|err| AuthError(err.to_string()))
+}
+
+/// Check `password` against a hash produced by [`hash_password`].
+pub fn verify_password(hash: &str, password: &str) -> Result<bool, AuthError> {
+    let parsed = PasswordHash::new(hash).map_err(|err| AuthError(err.to_string()))?;
+    Ok(Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+/// A fresh, random 256-bit session token, hex-encoded.
+pub fn generate_session_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_password_verifies_and_rejects_wrong_password() {
+        let hash = hash_password("correct horse battery staple").unwrap();
+        assert!(verify_password(&hash, "correct horse battery staple").unwrap());
+        assert!(!verify_password(&hash, "wrong password").unwrap());
+    }
+
+    #[test]
+    fn session_tokens_are_unique() {
+        assert_ne!(generate_session_token(), generate_session_token());
+    }
+
+    #[test]
+    fn password_hashed_with_custom_high_cost_params_still_verifies() {
+        let config = Argon2Config { memory_kib: 32 * 1024, iterations: 3, parallelism: 2 };
+        let hash = hash_password_with_config("correct horse battery staple", config).unwrap();
+        assert!(verify_password(&hash, "correct horse battery staple").unwrap());
+        assert!(!verify_password(&hash, "wrong password").unwrap());
+    }
+}
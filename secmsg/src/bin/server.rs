@@ -0,0 +1,34 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use secmsg::{build_router, AppState, Database};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[tokio::main]
+async fn main() {
+    let database_url = std::env::var("SECMSG_DATABASE_URL").unwrap_or_else(|_| "sqlite::memory:".to_string());
+    let db = Database::connect(&database_url).await.expect("failed to connect to database");
+
+    tokio::spawn(purge_expired_periodically(db.clone()));
+
+    let app = build_router(AppState::new(db));
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:8787").await.expect("failed to bind listener");
+    println!("secmsg listening on {}", listener.local_addr().unwrap());
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.expect("server error");
+}
+
+/// Periodically removes expired messages so the `messages` table doesn't
+/// grow forever.
+async fn purge_expired_periodically(db: Database) {
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match db.purge_expired(chrono::Utc::now()).await {
+            Ok(purged) if purged > 0 => println!("purged {purged} expired messages"),
+            Ok(_) => {}
+            Err(err) => eprintln!("failed to purge expired messages: {err}"),
+        }
+    }
+}
@@ -0,0 +1,748 @@
+//! X3DH key agreement and a simplified Double Ratchet for end-to-end
+//! encrypted messaging.
+//!
+//! This is Signal-protocol-inspired, not a literal implementation: it runs
+//! one X3DH handshake to derive a shared root key, then advances a
+//! symmetric-key ratchet (no further Diffie-Hellman step per message) to
+//! derive a fresh key for every message. That's enough to demonstrate
+//! forward secrecy within a session without the complexity of a full
+//! dual-ratchet state machine.
+
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chrono::{DateTime, Utc};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, SharedSecret, StaticSecret};
+use zeroize::Zeroize;
+
+/// Bump this to invalidate every HKDF derivation in this module at once
+/// (root key, ratchet chains, chain steps, fingerprints). Two sessions
+/// that disagree on `PROTOCOL_VERSION` derive different keys from the same
+/// inputs, so they can never be cross-version key-confused into sharing
+/// secrets.
+const PROTOCOL_VERSION: &str = "v1";
+
+/// Build the HKDF `info` parameter for `label`, scoped to
+/// [`PROTOCOL_VERSION`] so every derivation site gets the version prefix
+/// (and a distinct label) for free instead of a scattered literal.
+fn hkdf_info(version: &str, label: &str) -> Vec<u8> {
+    format!("secmsg-{version}-{label}").into_bytes()
+}
+
+/// Errors from key agreement, signing, or authenticated encryption.
+#[derive(Debug)]
+pub enum CryptoError {
+    InvalidSignature,
+    Decryption,
+    /// A [`RatchetState`] chain's message counter would overflow on the
+    /// next `encrypt`/`decrypt` call. A counter wrapping back to `0` would
+    /// reuse a message key, so the session must be re-keyed (e.g. a fresh
+    /// X3DH handshake) instead.
+    CounterOverflow,
+    /// A [`PreKeyBundle`]'s signed prekey is past its
+    /// [`PreKeyBundle::signed_prekey_expiry`] and must not be used to start
+    /// a new X3DH handshake.
+    ExpiredPreKey,
+    /// [`x3dh_respond`] was told the initiator used a one-time prekey
+    /// ([`X3DHInitiation::used_one_time_prekey`]) but wasn't given that
+    /// prekey's secret, so it can't derive a root key that agrees with the
+    /// initiator's.
+    MissingOneTimePrekey,
+    /// A [`RatchetMessage`] arrived with a `message_number` more than
+    /// [`MAX_SKIP`] positions ahead of the receiving chain, so
+    /// [`RatchetState::decrypt`] refused to derive and buffer that many
+    /// skipped-message keys.
+    TooManySkippedMessages,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::InvalidSignature => write!(f, "signature verification failed"),
+            CryptoError::Decryption => write!(f, "message could not be decrypted or authenticated"),
+            CryptoError::CounterOverflow => write!(f, "ratchet message counter would overflow; start a new session"),
+            CryptoError::ExpiredPreKey => write!(f, "signed prekey has expired; fetch a fresh bundle"),
+            CryptoError::MissingOneTimePrekey => {
+                write!(f, "initiator used a one-time prekey but no matching secret was supplied")
+            }
+            CryptoError::TooManySkippedMessages => {
+                write!(f, "message number is more than {MAX_SKIP} positions ahead of the receiving chain")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+/// A user's long-term identity: an Ed25519 signing keypair (authenticity)
+/// and an X25519 Diffie-Hellman keypair (key agreement).
+pub struct IdentityKeyPair {
+    pub signing_key: SigningKey,
+    pub dh_secret: StaticSecret,
+}
+
+impl IdentityKeyPair {
+    pub fn generate() -> Self {
+        IdentityKeyPair { signing_key: SigningKey::generate(&mut OsRng), dh_secret: StaticSecret::random_from_rng(OsRng) }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn dh_public(&self) -> PublicKey {
+        PublicKey::from(&self.dh_secret)
+    }
+}
+
+/// A published bundle of public keys a peer uses to start X3DH with this
+/// user without needing them online.
+pub struct PreKeyBundle {
+    pub identity_key: VerifyingKey,
+    pub identity_dh: PublicKey,
+    pub signed_prekey: PublicKey,
+    pub signed_prekey_signature: Signature,
+    pub one_time_prekey: Option<PublicKey>,
+    /// When this bundle's signed prekey was generated.
+    pub created_at: DateTime<Utc>,
+    /// When this bundle's signed prekey stops being eligible for new X3DH
+    /// handshakes. A server publishing bundles should refuse to hand out
+    /// ones failing [`PreKeyBundle::is_expired`], and [`x3dh_initiate`]
+    /// refuses to use one as a defense in depth.
+    pub signed_prekey_expiry: DateTime<Utc>,
+}
+
+impl PreKeyBundle {
+    /// Check that `signed_prekey` is actually signed by `identity_key`,
+    /// before it's trusted as a basis for key agreement.
+    pub fn verify(&self) -> Result<(), CryptoError> {
+        self.identity_key
+            .verify(self.signed_prekey.as_bytes(), &self.signed_prekey_signature)
+            .map_err(|_| CryptoError::InvalidSignature)
+    }
+
+    /// Whether [`signed_prekey_expiry`](Self::signed_prekey_expiry) has
+    /// passed as of `now`. A server holding this bundle should check this
+    /// before serving it to a peer, rather than waiting for the peer's own
+    /// [`x3dh_initiate`] check to reject it.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.signed_prekey_expiry
+    }
+}
+
+/// The shared secret both sides of an X3DH handshake agree on.
+pub type RootKey = [u8; 32];
+
+/// The result of running X3DH as the initiator: the derived root key, the
+/// ephemeral public key the responder needs to derive the same root key,
+/// and whether a one-time prekey was actually folded into the derivation
+/// (DH4). The initiator decides this purely from `bundle.one_time_prekey`,
+/// but the responder has no way to observe that decision on its own -- it
+/// must be told explicitly via [`used_one_time_prekey`](Self::used_one_time_prekey)
+/// and pass it to [`x3dh_respond`], rather than the two sides silently
+/// disagreeing about whether DH4 is part of the shared secret.
+#[derive(Debug, Clone)]
+pub struct X3DHInitiation {
+    pub root_key: RootKey,
+    pub ephemeral_public: PublicKey,
+    pub used_one_time_prekey: bool,
+}
+
+/// Run X3DH as the initiator against `bundle`, using this identity's own
+/// long-term DH key and a fresh ephemeral key.
+///
+/// Errors with [`CryptoError::ExpiredPreKey`] if `bundle`'s signed prekey
+/// is expired as of `now`, independent of whether its signature is valid.
+pub fn x3dh_initiate(identity: &IdentityKeyPair, bundle: &PreKeyBundle, now: DateTime<Utc>) -> Result<X3DHInitiation, CryptoError> {
+    bundle.verify()?;
+    if bundle.is_expired(now) {
+        return Err(CryptoError::ExpiredPreKey);
+    }
+    let ephemeral = StaticSecret::random_from_rng(OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral);
+
+    let dh1 = identity.dh_secret.diffie_hellman(&bundle.signed_prekey);
+    let dh2 = ephemeral.diffie_hellman(&bundle.identity_dh);
+    let dh3 = ephemeral.diffie_hellman(&bundle.signed_prekey);
+    let dh4 = bundle.one_time_prekey.as_ref().map(|otk| ephemeral.diffie_hellman(otk));
+    let used_one_time_prekey = dh4.is_some();
+
+    Ok(X3DHInitiation { root_key: derive_root_key(&dh1, &dh2, &dh3, dh4.as_ref()), ephemeral_public, used_one_time_prekey })
+}
+
+/// Run X3DH as the responder, given the initiator's identity DH and
+/// ephemeral public keys, this identity's own signed-prekey secret, and
+/// whatever one-time prekey secret it has on hand (if any).
+///
+/// `used_one_time_prekey` must be the initiator's own
+/// [`X3DHInitiation::used_one_time_prekey`], not inferred from whether
+/// `one_time_prekey_secret` happens to be `Some` -- that's what lets this
+/// side match DH4's inclusion to what the initiator actually did instead of
+/// silently deriving a mismatched root key. Errors with
+/// [`CryptoError::MissingOneTimePrekey`] if the initiator says it used one
+/// but `one_time_prekey_secret` is `None`; if the initiator says it
+/// *didn't*, any `one_time_prekey_secret` supplied is ignored so both sides
+/// still agree.
+pub fn x3dh_respond(
+    identity: &IdentityKeyPair,
+    signed_prekey_secret: &StaticSecret,
+    one_time_prekey_secret: Option<&StaticSecret>,
+    initiator_identity_dh: &PublicKey,
+    initiator_ephemeral: &PublicKey,
+    used_one_time_prekey: bool,
+) -> Result<RootKey, CryptoError> {
+    let dh1 = signed_prekey_secret.diffie_hellman(initiator_identity_dh);
+    let dh2 = identity.dh_secret.diffie_hellman(initiator_ephemeral);
+    let dh3 = signed_prekey_secret.diffie_hellman(initiator_ephemeral);
+    let dh4 = if used_one_time_prekey {
+        let otk = one_time_prekey_secret.ok_or(CryptoError::MissingOneTimePrekey)?;
+        Some(otk.diffie_hellman(initiator_ephemeral))
+    } else {
+        None
+    };
+
+    Ok(derive_root_key(&dh1, &dh2, &dh3, dh4.as_ref()))
+}
+
+fn derive_root_key(dh1: &SharedSecret, dh2: &SharedSecret, dh3: &SharedSecret, dh4: Option<&SharedSecret>) -> RootKey {
+    let mut ikm = Vec::with_capacity(32 * 4);
+    ikm.extend_from_slice(dh1.as_bytes());
+    ikm.extend_from_slice(dh2.as_bytes());
+    ikm.extend_from_slice(dh3.as_bytes());
+    if let Some(dh4) = dh4 {
+        ikm.extend_from_slice(dh4.as_bytes());
+    }
+
+    let hk = Hkdf::<Sha256>::new(None, &ikm);
+    let mut root_key = [0u8; 32];
+    hk.expand(&hkdf_info(PROTOCOL_VERSION, "x3dh-root-key"), &mut root_key).expect("32 is a valid HKDF-SHA256 output length");
+
+    ikm.zeroize();
+    root_key
+}
+
+/// One ratcheted, encrypted message, plus enough metadata for the receiver
+/// to decrypt and authenticate it.
+#[derive(Debug, Clone)]
+pub struct RatchetMessage {
+    pub message_number: u64,
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+    pub signature: Signature,
+}
+
+/// The most positions [`RatchetState::decrypt`] will advance the receiving
+/// chain over in one call to buffer skipped-message keys. A message
+/// claiming a larger gap is rejected with
+/// [`CryptoError::TooManySkippedMessages`] instead of being honored, since
+/// honoring it would derive (and buffer) one HKDF step per skipped
+/// position -- an attacker- or bug-controlled `message_number` far ahead of
+/// the receiving chain would otherwise cost unbounded CPU and memory per
+/// message. Matches the cap Signal's Double Ratchet implementation uses.
+const MAX_SKIP: u64 = 1000;
+
+/// Per-session ratchet state. Advances a symmetric chain key with every
+/// message sent or received, so each message is encrypted under its own
+/// key (forward secrecy within the session) -- see the module doc comment
+/// for how this differs from the full Double Ratchet spec.
+pub struct RatchetState {
+    sending_chain_key: [u8; 32],
+    receiving_chain_key: [u8; 32],
+    send_count: u64,
+    recv_count: u64,
+    /// Message keys for receiving-chain positions skipped over because a
+    /// later message arrived first, keyed by `message_number`. Drained by
+    /// [`RatchetState::decrypt`] as the skipped messages eventually show up.
+    skipped_keys: std::collections::HashMap<u64, [u8; 32]>,
+    /// The largest number of positions ever skipped over in one `decrypt`
+    /// call, for monitoring: a growing gap can indicate dropped messages or
+    /// an attacker probing the session.
+    max_skip_observed: u64,
+}
+
+impl RatchetState {
+    /// The side that ran [`x3dh_initiate`] sends on the chain the responder
+    /// receives on, and vice versa.
+    pub fn new_initiator(root_key: RootKey) -> Self {
+        let (sending, receiving) = Self::derive_chains(root_key);
+        RatchetState {
+            sending_chain_key: sending,
+            receiving_chain_key: receiving,
+            send_count: 0,
+            recv_count: 0,
+            skipped_keys: std::collections::HashMap::new(),
+            max_skip_observed: 0,
+        }
+    }
+
+    pub fn new_responder(root_key: RootKey) -> Self {
+        let (receiving, sending) = Self::derive_chains(root_key);
+        RatchetState {
+            sending_chain_key: sending,
+            receiving_chain_key: receiving,
+            send_count: 0,
+            recv_count: 0,
+            skipped_keys: std::collections::HashMap::new(),
+            max_skip_observed: 0,
+        }
+    }
+
+    /// How many receiving-chain message keys are currently buffered, waiting
+    /// for their out-of-order message to arrive.
+    pub fn skipped_key_count(&self) -> usize {
+        self.skipped_keys.len()
+    }
+
+    /// The largest gap ever seen between the next expected message and an
+    /// arriving one, across the life of this session. Never shrinks.
+    pub fn max_skip_observed(&self) -> u64 {
+        self.max_skip_observed
+    }
+
+    fn derive_chains(root_key: RootKey) -> ([u8; 32], [u8; 32]) {
+        let hk = Hkdf::<Sha256>::new(None, &root_key);
+        let mut okm = [0u8; 64];
+        hk.expand(&hkdf_info(PROTOCOL_VERSION, "ratchet-chains"), &mut okm).expect("64 is a valid HKDF-SHA256 output length");
+        let mut a = [0u8; 32];
+        let mut b = [0u8; 32];
+        a.copy_from_slice(&okm[..32]);
+        b.copy_from_slice(&okm[32..]);
+        (a, b)
+    }
+
+    fn advance(chain_key: &mut [u8; 32]) -> [u8; 32] {
+        let hk = Hkdf::<Sha256>::new(None, chain_key);
+        let mut okm = [0u8; 64];
+        hk.expand(&hkdf_info(PROTOCOL_VERSION, "chain-step"), &mut okm).expect("64 is a valid HKDF-SHA256 output length");
+        let mut message_key = [0u8; 32];
+        let mut next_chain_key = [0u8; 32];
+        message_key.copy_from_slice(&okm[..32]);
+        next_chain_key.copy_from_slice(&okm[32..]);
+        *chain_key = next_chain_key;
+        message_key
+    }
+
+    /// Encrypt `plaintext`, advancing the sending chain and signing the
+    /// message number plus ciphertext with `signing_key` so the recipient
+    /// (or a relay) can authenticate the sender.
+    ///
+    /// Errors with [`CryptoError::CounterOverflow`] instead of wrapping the
+    /// send counter back to `0`, which would reuse a message key.
+    pub fn encrypt(&mut self, plaintext: &[u8], signing_key: &SigningKey) -> Result<RatchetMessage, CryptoError> {
+        if self.send_count == u64::MAX {
+            return Err(CryptoError::CounterOverflow);
+        }
+
+        let message_key = Self::advance(&mut self.sending_chain_key);
+        let message_number = self.send_count;
+        self.send_count += 1;
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&message_key));
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let ciphertext =
+            cipher.encrypt(Nonce::from_slice(&nonce_bytes), plaintext).expect("encryption with a fresh key/nonce pair does not fail");
+
+        let signature = signing_key.sign(&signed_bytes(message_number, &ciphertext));
+        Ok(RatchetMessage { message_number, nonce: nonce_bytes, ciphertext, signature })
+    }
+
+    /// Decrypt `message`, verifying its signature against `sender_identity`
+    /// first. If `message` arrives out of order (its `message_number` is
+    /// ahead of the next expected one), the keys for the positions in
+    /// between are buffered (see [`RatchetState::skipped_key_count`]) rather
+    /// than lost, and consumed here if the missing message shows up later.
+    ///
+    /// Errors with [`CryptoError::CounterOverflow`] instead of wrapping the
+    /// receive counter back to `0`, which would reuse a message key.
+    ///
+    /// Errors with [`CryptoError::TooManySkippedMessages`] instead of
+    /// buffering the gap if `message`'s `message_number` is more than
+    /// [`MAX_SKIP`] positions ahead of the receiving chain.
+    pub fn decrypt(&mut self, message: &RatchetMessage, sender_identity: &VerifyingKey) -> Result<Vec<u8>, CryptoError> {
+        verify_ratchet_message(message, sender_identity)?;
+
+        if let Some(message_key) = self.skipped_keys.remove(&message.message_number) {
+            let cipher = ChaCha20Poly1305::new(Key::from_slice(&message_key));
+            return cipher.decrypt(Nonce::from_slice(&message.nonce), message.ciphertext.as_slice()).map_err(|_| CryptoError::Decryption);
+        }
+
+        if message.message_number < self.recv_count {
+            // Already consumed on this chain and not sitting in the skipped
+            // buffer -- a replay, not a late out-of-order arrival.
+            return Err(CryptoError::Decryption);
+        }
+
+        if self.recv_count == u64::MAX {
+            return Err(CryptoError::CounterOverflow);
+        }
+
+        let skip = message.message_number - self.recv_count;
+        if skip > MAX_SKIP {
+            return Err(CryptoError::TooManySkippedMessages);
+        }
+        self.max_skip_observed = self.max_skip_observed.max(skip);
+
+        while self.recv_count < message.message_number {
+            let skipped_key = Self::advance(&mut self.receiving_chain_key);
+            self.skipped_keys.insert(self.recv_count, skipped_key);
+            self.recv_count += 1;
+        }
+
+        let message_key = Self::advance(&mut self.receiving_chain_key);
+        self.recv_count += 1;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&message_key));
+        cipher.decrypt(Nonce::from_slice(&message.nonce), message.ciphertext.as_slice()).map_err(|_| CryptoError::Decryption)
+    }
+
+    /// How many messages have been sent on the current sending chain so
+    /// far, for deciding when to force a new session before
+    /// [`CryptoError::CounterOverflow`] becomes a concern.
+    pub fn messages_in_current_chain(&self) -> u64 {
+        self.send_count
+    }
+
+    /// A non-secret hash of the current sending and receiving chain keys.
+    /// Lets a test demonstrate that the ratchet state actually changes --
+    /// e.g. after exchanging messages, or after recovering from a
+    /// compromise by starting a fresh session as recommended in
+    /// [`CryptoError::CounterOverflow`]'s docs -- without exposing the
+    /// chain keys themselves.
+    pub fn current_chain_keys_fingerprint(&self) -> [u8; 32] {
+        let hk = Hkdf::<Sha256>::new(None, &[self.sending_chain_key, self.receiving_chain_key].concat());
+        let mut fingerprint = [0u8; 32];
+        hk.expand(&hkdf_info(PROTOCOL_VERSION, "chain-key-fingerprint"), &mut fingerprint).expect("32 is a valid HKDF-SHA256 output length");
+        fingerprint
+    }
+}
+
+/// Check `msg`'s signature against `sender_identity`, independent of any
+/// [`RatchetState`]. Lets a relay that never holds ratchet (or even
+/// plaintext) state reject forged messages at ingress, since the signature
+/// covers the message number and ciphertext but requires no decryption key.
+pub fn verify_ratchet_message(msg: &RatchetMessage, sender_identity: &VerifyingKey) -> Result<(), CryptoError> {
+    sender_identity.verify(&signed_bytes(msg.message_number, &msg.ciphertext), &msg.signature).map_err(|_| CryptoError::InvalidSignature)
+}
+
+fn signed_bytes(message_number: u64, ciphertext: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(8 + ciphertext.len());
+    bytes.extend_from_slice(&message_number.to_be_bytes());
+    bytes.extend_from_slice(ciphertext);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake() -> (IdentityKeyPair, IdentityKeyPair, RatchetState, RatchetState) {
+        let alice = IdentityKeyPair::generate();
+        let bob = IdentityKeyPair::generate();
+
+        let bob_signed_prekey_secret = StaticSecret::random_from_rng(OsRng);
+        let bob_signed_prekey = PublicKey::from(&bob_signed_prekey_secret);
+        let signature = bob.signing_key.sign(bob_signed_prekey.as_bytes());
+
+        let bundle = PreKeyBundle {
+            identity_key: bob.verifying_key(),
+            identity_dh: bob.dh_public(),
+            signed_prekey: bob_signed_prekey,
+            signed_prekey_signature: signature,
+            one_time_prekey: None,
+            created_at: Utc::now(),
+            signed_prekey_expiry: Utc::now() + chrono::Duration::days(7),
+        };
+
+        let initiation = x3dh_initiate(&alice, &bundle, Utc::now()).unwrap();
+        let bob_root = x3dh_respond(
+            &bob,
+            &bob_signed_prekey_secret,
+            None,
+            &alice.dh_public(),
+            &initiation.ephemeral_public,
+            initiation.used_one_time_prekey,
+        )
+        .unwrap();
+        assert_eq!(initiation.root_key, bob_root);
+
+        let alice_ratchet = RatchetState::new_initiator(initiation.root_key);
+        let bob_ratchet = RatchetState::new_responder(bob_root);
+        (alice, bob, alice_ratchet, bob_ratchet)
+    }
+
+    #[test]
+    fn both_sides_derive_matching_root_key_and_decrypt_each_others_messages() {
+        let (alice, bob, mut alice_ratchet, mut bob_ratchet) = handshake();
+
+        let msg = alice_ratchet.encrypt(b"hello bob", &alice.signing_key).unwrap();
+        let plaintext = bob_ratchet.decrypt(&msg, &alice.verifying_key()).unwrap();
+        assert_eq!(plaintext, b"hello bob");
+
+        let reply = bob_ratchet.encrypt(b"hi alice", &bob.signing_key).unwrap();
+        let plaintext = alice_ratchet.decrypt(&reply, &bob.verifying_key()).unwrap();
+        assert_eq!(plaintext, b"hi alice");
+    }
+
+    /// Run a full initiator/responder handshake against a bundle that
+    /// either offers a one-time prekey or doesn't, asserting both sides
+    /// agree on the resulting root key either way.
+    fn agree(one_time_prekey: Option<(PublicKey, StaticSecret)>) -> RootKey {
+        let alice = IdentityKeyPair::generate();
+        let bob = IdentityKeyPair::generate();
+
+        let bob_signed_prekey_secret = StaticSecret::random_from_rng(OsRng);
+        let bob_signed_prekey = PublicKey::from(&bob_signed_prekey_secret);
+        let signature = bob.signing_key.sign(bob_signed_prekey.as_bytes());
+        let (bob_otk_public, bob_otk_secret) = match &one_time_prekey {
+            Some((public, secret)) => (Some(*public), Some(secret)),
+            None => (None, None),
+        };
+
+        let bundle = PreKeyBundle {
+            identity_key: bob.verifying_key(),
+            identity_dh: bob.dh_public(),
+            signed_prekey: bob_signed_prekey,
+            signed_prekey_signature: signature,
+            one_time_prekey: bob_otk_public,
+            created_at: Utc::now(),
+            signed_prekey_expiry: Utc::now() + chrono::Duration::days(7),
+        };
+
+        let initiation = x3dh_initiate(&alice, &bundle, Utc::now()).unwrap();
+        let bob_root = x3dh_respond(
+            &bob,
+            &bob_signed_prekey_secret,
+            bob_otk_secret,
+            &alice.dh_public(),
+            &initiation.ephemeral_public,
+            initiation.used_one_time_prekey,
+        )
+        .unwrap();
+
+        assert_eq!(initiation.root_key, bob_root);
+        initiation.root_key
+    }
+
+    #[test]
+    fn agreement_succeeds_both_with_and_without_a_one_time_prekey() {
+        let without_otk = agree(None);
+
+        let otk_secret = StaticSecret::random_from_rng(OsRng);
+        let otk_public = PublicKey::from(&otk_secret);
+        let with_otk = agree(Some((otk_public, otk_secret)));
+
+        // The two handshakes are independent (fresh identities and
+        // ephemerals each time), so there's no reason for their root keys
+        // to collide; the real assertion already happened inside `agree`.
+        assert_ne!(without_otk, with_otk);
+    }
+
+    #[test]
+    fn responder_errors_instead_of_silently_mismatching_when_told_a_one_time_prekey_was_used_but_none_was_supplied() {
+        let alice = IdentityKeyPair::generate();
+        let bob = IdentityKeyPair::generate();
+
+        let bob_signed_prekey_secret = StaticSecret::random_from_rng(OsRng);
+        let bob_signed_prekey = PublicKey::from(&bob_signed_prekey_secret);
+        let signature = bob.signing_key.sign(bob_signed_prekey.as_bytes());
+        let otk_secret = StaticSecret::random_from_rng(OsRng);
+        let otk_public = PublicKey::from(&otk_secret);
+
+        let bundle = PreKeyBundle {
+            identity_key: bob.verifying_key(),
+            identity_dh: bob.dh_public(),
+            signed_prekey: bob_signed_prekey,
+            signed_prekey_signature: signature,
+            one_time_prekey: Some(otk_public),
+            created_at: Utc::now(),
+            signed_prekey_expiry: Utc::now() + chrono::Duration::days(7),
+        };
+
+        let initiation = x3dh_initiate(&alice, &bundle, Utc::now()).unwrap();
+        assert!(initiation.used_one_time_prekey);
+
+        let result = x3dh_respond(
+            &bob,
+            &bob_signed_prekey_secret,
+            None,
+            &alice.dh_public(),
+            &initiation.ephemeral_public,
+            initiation.used_one_time_prekey,
+        );
+        assert!(matches!(result, Err(CryptoError::MissingOneTimePrekey)));
+    }
+
+    #[test]
+    fn flipped_ciphertext_byte_fails_to_decrypt() {
+        let (alice, _bob, mut alice_ratchet, mut bob_ratchet) = handshake();
+
+        let mut msg = alice_ratchet.encrypt(b"hello bob", &alice.signing_key).unwrap();
+        msg.ciphertext[0] ^= 0x01;
+
+        let result = bob_ratchet.decrypt(&msg, &alice.verifying_key());
+        assert!(matches!(result, Err(CryptoError::InvalidSignature) | Err(CryptoError::Decryption)));
+    }
+
+    #[test]
+    fn verify_ratchet_message_rejects_a_flipped_ciphertext_byte() {
+        let (alice, _bob, mut alice_ratchet, _bob_ratchet) = handshake();
+
+        let mut msg = alice_ratchet.encrypt(b"hello bob", &alice.signing_key).unwrap();
+        assert!(verify_ratchet_message(&msg, &alice.verifying_key()).is_ok());
+
+        msg.ciphertext[0] ^= 0x01;
+        assert!(matches!(verify_ratchet_message(&msg, &alice.verifying_key()), Err(CryptoError::InvalidSignature)));
+    }
+
+    #[test]
+    fn encrypting_at_the_counter_limit_errors_instead_of_wrapping() {
+        let (alice, _bob, mut alice_ratchet, _bob_ratchet) = handshake();
+        alice_ratchet.send_count = u64::MAX;
+
+        let result = alice_ratchet.encrypt(b"one too many", &alice.signing_key);
+        assert!(matches!(result, Err(CryptoError::CounterOverflow)));
+        assert_eq!(alice_ratchet.messages_in_current_chain(), u64::MAX);
+    }
+
+    #[test]
+    fn chain_key_fingerprint_changes_after_each_message_and_after_a_post_compromise_rekey() {
+        let (alice, _bob, mut alice_ratchet, mut bob_ratchet) = handshake();
+
+        let fingerprint_before = alice_ratchet.current_chain_keys_fingerprint();
+
+        let msg = alice_ratchet.encrypt(b"hello bob", &alice.signing_key).unwrap();
+        bob_ratchet.decrypt(&msg, &alice.verifying_key()).unwrap();
+        let fingerprint_after_send = alice_ratchet.current_chain_keys_fingerprint();
+        assert_ne!(fingerprint_before, fingerprint_after_send, "sending a message must advance the sending chain key");
+
+        // Simulate recovering from a compromise of `alice_ratchet` by starting
+        // a brand new session -- a fresh X3DH handshake, i.e. a fresh
+        // Diffie-Hellman agreement -- as `CryptoError::CounterOverflow`'s
+        // docs already recommend for re-keying.
+        let (_, _, alice_ratchet_recovered, _bob_ratchet_recovered) = handshake();
+        let fingerprint_recovered = alice_ratchet_recovered.current_chain_keys_fingerprint();
+        assert_ne!(
+            fingerprint_after_send, fingerprint_recovered,
+            "a fresh post-compromise session must not reuse the compromised chain keys"
+        );
+    }
+
+    #[test]
+    fn out_of_order_decryption_buffers_skipped_keys_and_drains_them_as_theyre_consumed() {
+        let (alice, _bob, mut alice_ratchet, mut bob_ratchet) = handshake();
+
+        let msg0 = alice_ratchet.encrypt(b"zero", &alice.signing_key).unwrap();
+        let msg1 = alice_ratchet.encrypt(b"one", &alice.signing_key).unwrap();
+        let msg2 = alice_ratchet.encrypt(b"two", &alice.signing_key).unwrap();
+
+        assert_eq!(bob_ratchet.skipped_key_count(), 0);
+        assert_eq!(bob_ratchet.max_skip_observed(), 0);
+
+        // msg2 arrives first, skipping over msg0 and msg1's positions.
+        let plaintext = bob_ratchet.decrypt(&msg2, &alice.verifying_key()).unwrap();
+        assert_eq!(plaintext, b"two");
+        assert_eq!(bob_ratchet.skipped_key_count(), 2, "msg0 and msg1's keys should be buffered");
+        assert_eq!(bob_ratchet.max_skip_observed(), 2);
+
+        let plaintext = bob_ratchet.decrypt(&msg0, &alice.verifying_key()).unwrap();
+        assert_eq!(plaintext, b"zero");
+        assert_eq!(bob_ratchet.skipped_key_count(), 1, "only msg1's key should remain buffered");
+
+        let plaintext = bob_ratchet.decrypt(&msg1, &alice.verifying_key()).unwrap();
+        assert_eq!(plaintext, b"one");
+        assert_eq!(bob_ratchet.skipped_key_count(), 0, "all skipped keys should be consumed");
+        assert_eq!(bob_ratchet.max_skip_observed(), 2, "the high-water mark must not drop back down");
+    }
+
+    #[test]
+    fn message_number_far_ahead_of_the_receiving_chain_is_rejected_instead_of_buffered() {
+        let (alice, _bob, mut alice_ratchet, mut bob_ratchet) = handshake();
+
+        for _ in 0..=MAX_SKIP {
+            alice_ratchet.encrypt(b"filler", &alice.signing_key).unwrap();
+        }
+        // message_number MAX_SKIP + 1, i.e. one more than MAX_SKIP ahead of
+        // bob's still-zero recv_count.
+        let far_ahead = alice_ratchet.encrypt(b"far ahead", &alice.signing_key).unwrap();
+
+        let result = bob_ratchet.decrypt(&far_ahead, &alice.verifying_key());
+        assert!(matches!(result, Err(CryptoError::TooManySkippedMessages)));
+        assert_eq!(bob_ratchet.skipped_key_count(), 0, "a rejected gap must not be buffered at all");
+    }
+
+    #[test]
+    fn hkdf_info_differs_by_label_and_by_protocol_version() {
+        assert_ne!(hkdf_info(PROTOCOL_VERSION, "x3dh-root-key"), hkdf_info(PROTOCOL_VERSION, "ratchet-chains"));
+        assert_ne!(hkdf_info(PROTOCOL_VERSION, "x3dh-root-key"), hkdf_info("v2", "x3dh-root-key"));
+    }
+
+    #[test]
+    fn changing_the_protocol_version_changes_every_derived_key() {
+        let ikm = [0x42u8; 32];
+
+        let hk_v1 = Hkdf::<Sha256>::new(None, &ikm);
+        let mut root_key_v1 = [0u8; 32];
+        hk_v1.expand(&hkdf_info(PROTOCOL_VERSION, "x3dh-root-key"), &mut root_key_v1).unwrap();
+
+        let hk_v2 = Hkdf::<Sha256>::new(None, &ikm);
+        let mut root_key_v2 = [0u8; 32];
+        hk_v2.expand(&hkdf_info("v2", "x3dh-root-key"), &mut root_key_v2).unwrap();
+
+        assert_ne!(root_key_v1, root_key_v2, "two sides that disagree on the protocol version must not derive the same shared secret");
+    }
+
+    #[test]
+    fn tampered_bundle_signature_is_rejected() {
+        let bob = IdentityKeyPair::generate();
+        let mallory = IdentityKeyPair::generate();
+        let bob_signed_prekey_secret = StaticSecret::random_from_rng(OsRng);
+        let bob_signed_prekey = PublicKey::from(&bob_signed_prekey_secret);
+
+        let bundle = PreKeyBundle {
+            identity_key: bob.verifying_key(),
+            identity_dh: bob.dh_public(),
+            signed_prekey: bob_signed_prekey,
+            signed_prekey_signature: mallory.signing_key.sign(bob_signed_prekey.as_bytes()),
+            one_time_prekey: None,
+            created_at: Utc::now(),
+            signed_prekey_expiry: Utc::now() + chrono::Duration::days(7),
+        };
+
+        assert!(bundle.verify().is_err());
+    }
+
+    #[test]
+    fn x3dh_initiate_rejects_an_expired_bundle_but_accepts_a_fresh_one() {
+        let alice = IdentityKeyPair::generate();
+        let bob = IdentityKeyPair::generate();
+        let bob_signed_prekey_secret = StaticSecret::random_from_rng(OsRng);
+        let bob_signed_prekey = PublicKey::from(&bob_signed_prekey_secret);
+        let signature = bob.signing_key.sign(bob_signed_prekey.as_bytes());
+
+        let created_at = Utc::now() - chrono::Duration::days(10);
+        let bundle = PreKeyBundle {
+            identity_key: bob.verifying_key(),
+            identity_dh: bob.dh_public(),
+            signed_prekey: bob_signed_prekey,
+            signed_prekey_signature: signature,
+            one_time_prekey: None,
+            created_at,
+            signed_prekey_expiry: created_at + chrono::Duration::days(7),
+        };
+
+        let now = Utc::now();
+        assert!(bundle.is_expired(now));
+        assert!(matches!(x3dh_initiate(&alice, &bundle, now), Err(CryptoError::ExpiredPreKey)));
+
+        let fresh_expiry = now + chrono::Duration::days(7);
+        let fresh_bundle = PreKeyBundle { signed_prekey_expiry: fresh_expiry, ..bundle };
+        assert!(!fresh_bundle.is_expired(now));
+        assert!(x3dh_initiate(&alice, &fresh_bundle, now).is_ok());
+    }
+}
@@ -0,0 +1,726 @@
+//! Persistence for accounts, prekeys, sessions, and queued messages,
+//! backed by SQLite through `sqlx`.
+
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::FromRow;
+
+/// Errors from a database operation.
+#[derive(Debug)]
+pub enum DbError {
+    /// A failure retrying won't fix: bad SQL, a violated constraint, a
+    /// closed pool, etc.
+    Sqlx(sqlx::Error),
+    /// A failure likely to succeed if retried shortly after: SQLite's
+    /// writer lock was briefly held by another connection
+    /// (`SQLITE_BUSY`/`SQLITE_LOCKED`). [`Database`]'s write methods retry
+    /// these automatically with backoff, per [`RetryConfig`].
+    Transient(sqlx::Error),
+    NotFound,
+}
+
+impl DbError {
+    /// Whether this error is [`DbError::Transient`] and therefore worth
+    /// retrying, as opposed to a permanent [`DbError::Sqlx`] failure or a
+    /// [`DbError::NotFound`] that retrying can't fix.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DbError::Transient(_))
+    }
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Sqlx(err) => write!(f, "database error: {err}"),
+            DbError::Transient(err) => write!(f, "transient database error (retryable): {err}"),
+            DbError::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DbError::Sqlx(err) | DbError::Transient(err) => Some(err),
+            DbError::NotFound => None,
+        }
+    }
+}
+
+/// SQLite result codes indicating the writer lock was briefly unavailable:
+/// `SQLITE_BUSY` (5) and `SQLITE_LOCKED` (6). See
+/// <https://www.sqlite.org/rescode.html>.
+fn is_transient_sqlite_error(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Database(db_err) if matches!(db_err.code().as_deref(), Some("5") | Some("6")))
+}
+
+impl From<sqlx::Error> for DbError {
+    fn from(err: sqlx::Error) -> Self {
+        if is_transient_sqlite_error(&err) {
+            DbError::Transient(err)
+        } else {
+            DbError::Sqlx(err)
+        }
+    }
+}
+
+/// The fields needed to register a new account, grouped to keep
+/// [`Database::register_user`]'s signature manageable.
+pub struct NewUser<'a> {
+    pub id: &'a str,
+    pub username: &'a str,
+    pub password_hash: &'a str,
+    pub identity_key: &'a str,
+    pub identity_dh: &'a str,
+    pub signed_prekey: &'a str,
+    pub signed_prekey_signature: &'a str,
+}
+
+/// An account, with its password hash and X3DH identity material. Key
+/// material is stored base64-encoded text, matching the shape the HTTP API
+/// exchanges with clients.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserRecord {
+    pub id: String,
+    pub username: String,
+    pub password_hash: String,
+    pub identity_key: String,
+    pub identity_dh: String,
+    pub signed_prekey: String,
+    pub signed_prekey_signature: String,
+}
+
+/// Non-sensitive fields about a user, returned by
+/// [`Database::get_public_user`] for directory lookups. Deliberately
+/// excludes the password hash and X3DH key material that [`UserRecord`]
+/// carries -- a lookup only needs to tell a caller whether the username
+/// exists and has prekeys to hand out, not the keys themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicUserInfo {
+    pub id: String,
+    pub has_one_time_prekeys: bool,
+}
+
+/// A queued, still-encrypted message, as stored for a recipient.
+#[derive(Debug, Clone, FromRow)]
+pub struct StoredMessage {
+    pub id: i64,
+    pub sender_id: String,
+    pub message_number: i64,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One schema change, applied at most once and recorded in
+/// `schema_migrations` by [`Database::migrate`] so restarts don't re-run
+/// (or lose track of) earlier versions.
+struct Migration {
+    version: i64,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        sql: "CREATE TABLE IF NOT EXISTS users (
+            id TEXT PRIMARY KEY,
+            username TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            identity_key TEXT NOT NULL,
+            identity_dh TEXT NOT NULL,
+            signed_prekey TEXT NOT NULL,
+            signed_prekey_signature TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+    },
+    Migration {
+        version: 2,
+        sql: "CREATE TABLE IF NOT EXISTS one_time_prekeys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT NOT NULL REFERENCES users(id),
+            public_key TEXT NOT NULL,
+            consumed INTEGER NOT NULL DEFAULT 0
+        )",
+    },
+    Migration {
+        version: 3,
+        sql: "CREATE TABLE IF NOT EXISTS sessions (
+            token TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL REFERENCES users(id),
+            created_at TEXT NOT NULL
+        )",
+    },
+    Migration {
+        version: 4,
+        sql: "CREATE TABLE IF NOT EXISTS messages (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            recipient_id TEXT NOT NULL REFERENCES users(id),
+            sender_id TEXT NOT NULL,
+            message_number INTEGER NOT NULL,
+            nonce TEXT NOT NULL,
+            ciphertext TEXT NOT NULL,
+            signature TEXT NOT NULL,
+            delivered INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        )",
+    },
+];
+
+/// How long messages are kept before [`Database::purge_expired`] removes
+/// them: delivered messages have already served their purpose and can go
+/// sooner, while undelivered ones are kept longer in case the recipient is
+/// offline for a while.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub delivered_ttl: Duration,
+    pub undelivered_ttl: Duration,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy { delivered_ttl: Duration::days(7), undelivered_ttl: Duration::days(30) }
+    }
+}
+
+/// Connection pool tuning for [`Database::connect_with_config`]. The
+/// defaults are fine for the in-memory test pool; production deployments
+/// under real concurrent load should raise `max_connections` and may want
+/// a longer `busy_timeout` to ride out SQLite's single-writer lock instead
+/// of failing a request outright.
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+    pub busy_timeout: StdDuration,
+    pub retry: RetryConfig,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig { max_connections: 5, busy_timeout: StdDuration::from_secs(5), retry: RetryConfig::default() }
+    }
+}
+
+/// How [`Database`]'s write methods retry a [`DbError::Transient`] failure:
+/// up to `max_retries` attempts, waiting `initial_backoff *
+/// backoff_multiplier.pow(attempt)` between each. A [`DbError::Sqlx`] or
+/// [`DbError::NotFound`] is never retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub initial_backoff: StdDuration,
+    pub backoff_multiplier: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig { max_retries: 3, initial_backoff: StdDuration::from_millis(10), backoff_multiplier: 2.0 }
+    }
+}
+
+/// A handle to the message server's database. Cheap to clone: it shares
+/// the underlying connection pool.
+#[derive(Clone)]
+pub struct Database {
+    pool: SqlitePool,
+    retention: RetentionPolicy,
+    retry: RetryConfig,
+}
+
+impl Database {
+    /// Connect to `url` (e.g. `sqlite::memory:` or `sqlite:secmsg.db`) and
+    /// ensure the schema is up to date, using the [`RetentionPolicy::default`]
+    /// and [`DatabaseConfig::default`].
+    pub async fn connect(url: &str) -> Result<Self, DbError> {
+        Self::connect_with_config(url, RetentionPolicy::default(), DatabaseConfig::default()).await
+    }
+
+    /// Like [`Database::connect`], but with a custom [`RetentionPolicy`].
+    pub async fn connect_with_retention(url: &str, retention: RetentionPolicy) -> Result<Self, DbError> {
+        Self::connect_with_config(url, retention, DatabaseConfig::default()).await
+    }
+
+    /// Like [`Database::connect`], but with a custom [`RetentionPolicy`] and
+    /// [`DatabaseConfig`].
+    pub async fn connect_with_config(url: &str, retention: RetentionPolicy, config: DatabaseConfig) -> Result<Self, DbError> {
+        let connect_options = SqliteConnectOptions::from_str(url).map_err(DbError::Sqlx)?.busy_timeout(config.busy_timeout);
+        let pool = SqlitePoolOptions::new().max_connections(config.max_connections).connect_with(connect_options).await?;
+        let db = Database { pool, retention, retry: config.retry };
+        db.migrate().await?;
+        Ok(db)
+    }
+
+    /// Retry `operation` while it returns a [`DbError::is_transient`]
+    /// error, waiting with exponential backoff between attempts per
+    /// [`RetryConfig`], up to `self.retry.max_retries` retries.
+    async fn with_retry<T, F, Fut>(&self, operation: F) -> Result<T, DbError>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: Future<Output = Result<T, DbError>> + Send,
+        T: Send,
+    {
+        self.with_retry_and_hook(operation, &mut |_attempt, _delay| {}).await
+    }
+
+    /// Like [`Database::with_retry`], but calls `on_retry(attempt, delay)`
+    /// right before each backoff sleep. Exists so tests can observe that a
+    /// transient error gets retried (and a permanent one doesn't) without
+    /// needing to trigger real SQLite contention.
+    async fn with_retry_and_hook<T, F, Fut>(
+        &self,
+        mut operation: F,
+        on_retry: &mut (dyn FnMut(u32, StdDuration) + Send),
+    ) -> Result<T, DbError>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: Future<Output = Result<T, DbError>> + Send,
+        T: Send,
+    {
+        let mut backoff = self.retry.initial_backoff;
+        for attempt in 0..=self.retry.max_retries {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(err) if err.is_transient() && attempt < self.retry.max_retries => {
+                    on_retry(attempt, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.mul_f64(self.retry.backoff_multiplier);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        unreachable!("the loop above always returns by its final (attempt == max_retries) iteration")
+    }
+
+    /// Apply every [`MIGRATIONS`] entry not yet recorded in
+    /// `schema_migrations`, in version order. Safe to call repeatedly --
+    /// already-applied versions are skipped, so re-running on an
+    /// up-to-date database is a no-op.
+    async fn migrate(&self) -> Result<(), DbError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                applied_at TEXT NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        for migration in MIGRATIONS {
+            let already_applied: Option<(i64,)> =
+                sqlx::query_as("SELECT version FROM schema_migrations WHERE version = ?").bind(migration.version).fetch_optional(&self.pool).await?;
+            if already_applied.is_some() {
+                continue;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query(migration.sql).execute(&mut *tx).await?;
+            sqlx::query("INSERT INTO schema_migrations (version, applied_at) VALUES (?, ?)")
+                .bind(migration.version)
+                .bind(Utc::now().to_rfc3339())
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn register_user(&self, user: NewUser<'_>) -> Result<(), DbError> {
+        let user = &user;
+        self.with_retry(|| async {
+            sqlx::query(
+                "INSERT INTO users (id, username, password_hash, identity_key, identity_dh, signed_prekey, signed_prekey_signature, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(user.id)
+            .bind(user.username)
+            .bind(user.password_hash)
+            .bind(user.identity_key)
+            .bind(user.identity_dh)
+            .bind(user.signed_prekey)
+            .bind(user.signed_prekey_signature)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn find_user_by_username(&self, username: &str) -> Result<Option<UserRecord>, DbError> {
+        let row = sqlx::query_as::<_, UserRecord>(
+            "SELECT id, username, password_hash, identity_key, identity_dh, signed_prekey, signed_prekey_signature FROM users WHERE username = ?",
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    /// Look up `username` for a directory lookup: an exact match only, with
+    /// no wildcard or prefix search that would let a caller enumerate the
+    /// user base. Always runs both the user lookup and the
+    /// `one_time_prekeys` existence check, whether or not `username`
+    /// exists -- the latter against a dummy id that can never match a real
+    /// `user_id` when it doesn't -- so the same two indexed queries happen
+    /// on every call and a caller can't infer existence from timing.
+    pub async fn get_public_user(&self, username: &str) -> Result<Option<PublicUserInfo>, DbError> {
+        let user = self.find_user_by_username(username).await?;
+        let lookup_id = user.as_ref().map(|u| u.id.as_str()).unwrap_or("");
+
+        let (has_one_time_prekeys,): (i64,) =
+            sqlx::query_as("SELECT EXISTS(SELECT 1 FROM one_time_prekeys WHERE user_id = ? AND consumed = 0)").bind(lookup_id).fetch_one(&self.pool).await?;
+
+        Ok(user.map(|user| PublicUserInfo { id: user.id, has_one_time_prekeys: has_one_time_prekeys != 0 }))
+    }
+
+    pub async fn add_one_time_prekey(&self, user_id: &str, public_key: &str) -> Result<(), DbError> {
+        self.with_retry(|| async {
+            sqlx::query("INSERT INTO one_time_prekeys (user_id, public_key, consumed) VALUES (?, ?, 0)")
+                .bind(user_id)
+                .bind(public_key)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Atomically claim and return one of `user_id`'s unconsumed one-time
+    /// prekeys, or `None` if they've run out.
+    pub async fn take_one_time_prekey(&self, user_id: &str) -> Result<Option<String>, DbError> {
+        self.with_retry(|| async {
+            let mut tx = self.pool.begin().await?;
+            let row: Option<(i64, String)> =
+                sqlx::query_as("SELECT id, public_key FROM one_time_prekeys WHERE user_id = ? AND consumed = 0 LIMIT 1")
+                    .bind(user_id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+            let Some((id, public_key)) = row else {
+                tx.commit().await?;
+                return Ok(None);
+            };
+
+            sqlx::query("UPDATE one_time_prekeys SET consumed = 1 WHERE id = ?").bind(id).execute(&mut *tx).await?;
+            tx.commit().await?;
+            Ok(Some(public_key))
+        })
+        .await
+    }
+
+    pub async fn create_session(&self, token: &str, user_id: &str) -> Result<(), DbError> {
+        self.with_retry(|| async {
+            sqlx::query("INSERT INTO sessions (token, user_id, created_at) VALUES (?, ?, ?)")
+                .bind(token)
+                .bind(user_id)
+                .bind(Utc::now().to_rfc3339())
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn session_user(&self, token: &str) -> Result<Option<String>, DbError> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT user_id FROM sessions WHERE token = ?").bind(token).fetch_optional(&self.pool).await?;
+        Ok(row.map(|(user_id,)| user_id))
+    }
+
+    pub async fn store_message(
+        &self,
+        recipient_id: &str,
+        sender_id: &str,
+        message_number: i64,
+        nonce: &str,
+        ciphertext: &str,
+        signature: &str,
+    ) -> Result<i64, DbError> {
+        self.with_retry(|| async {
+            let result = sqlx::query(
+                "INSERT INTO messages (recipient_id, sender_id, message_number, nonce, ciphertext, signature, delivered, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, 0, ?)",
+            )
+            .bind(recipient_id)
+            .bind(sender_id)
+            .bind(message_number)
+            .bind(nonce)
+            .bind(ciphertext)
+            .bind(signature)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+            Ok(result.last_insert_rowid())
+        })
+        .await
+    }
+
+    /// Every message queued for `recipient_id` that hasn't been delivered
+    /// yet, oldest first.
+    pub async fn fetch_undelivered(&self, recipient_id: &str) -> Result<Vec<StoredMessage>, DbError> {
+        let rows = sqlx::query_as::<_, StoredMessage>(
+            "SELECT id, sender_id, message_number, nonce, ciphertext, signature, created_at
+             FROM messages WHERE recipient_id = ? AND delivered = 0 ORDER BY id ASC",
+        )
+        .bind(recipient_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn mark_delivered(&self, message_ids: &[i64]) -> Result<(), DbError> {
+        self.with_retry(|| async {
+            for id in message_ids {
+                sqlx::query("UPDATE messages SET delivered = 1 WHERE id = ?").bind(id).execute(&self.pool).await?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Delete delivered messages older than the policy's `delivered_ttl`
+    /// and undelivered messages older than its `undelivered_ttl`, relative
+    /// to `now`. Returns the number of rows removed.
+    pub async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64, DbError> {
+        let delivered_cutoff = (now - self.retention.delivered_ttl).to_rfc3339();
+        let undelivered_cutoff = (now - self.retention.undelivered_ttl).to_rfc3339();
+
+        self.with_retry(|| async {
+            let delivered_result =
+                sqlx::query("DELETE FROM messages WHERE delivered = 1 AND created_at < ?").bind(&delivered_cutoff).execute(&self.pool).await?;
+            let undelivered_result =
+                sqlx::query("DELETE FROM messages WHERE delivered = 0 AND created_at < ?").bind(&undelivered_cutoff).execute(&self.pool).await?;
+
+            Ok(delivered_result.rows_affected() + undelivered_result.rows_affected())
+        })
+        .await
+    }
+
+    /// Delete `user_id`'s account along with everything referencing it:
+    /// one-time prekeys, sessions, and messages sent or received by the
+    /// user. Runs as a single transaction so the cascade is all-or-nothing.
+    pub async fn delete_user(&self, user_id: &str) -> Result<(), DbError> {
+        self.with_retry(|| async {
+            let mut tx = self.pool.begin().await?;
+
+            sqlx::query("DELETE FROM messages WHERE sender_id = ? OR recipient_id = ?").bind(user_id).bind(user_id).execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM one_time_prekeys WHERE user_id = ?").bind(user_id).execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM sessions WHERE user_id = ?").bind(user_id).execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM users WHERE id = ?").bind(user_id).execute(&mut *tx).await?;
+
+            tx.commit().await?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_db() -> Database {
+        Database::connect("sqlite::memory:").await.unwrap()
+    }
+
+    fn new_user<'a>(id: &'a str, username: &'a str) -> NewUser<'a> {
+        NewUser { id, username, password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" }
+    }
+
+    #[tokio::test]
+    async fn registered_user_is_found_by_username() {
+        let db = test_db().await;
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+
+        let user = db.find_user_by_username("alice").await.unwrap().unwrap();
+        assert_eq!(user.id, "u1");
+        assert_eq!(user.password_hash, "hash");
+    }
+
+    #[tokio::test]
+    async fn one_time_prekey_is_consumed_exactly_once() {
+        let db = test_db().await;
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+        db.add_one_time_prekey("u1", "otk-1").await.unwrap();
+
+        assert_eq!(db.take_one_time_prekey("u1").await.unwrap(), Some("otk-1".to_string()));
+        assert_eq!(db.take_one_time_prekey("u1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn stored_message_shows_up_as_undelivered_until_marked() {
+        let db = test_db().await;
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+        db.register_user(new_user("u2", "bob")).await.unwrap();
+
+        let id = db.store_message("u2", "u1", 0, "nonce", "cipher", "sig").await.unwrap();
+        let undelivered = db.fetch_undelivered("u2").await.unwrap();
+        assert_eq!(undelivered.len(), 1);
+        assert_eq!(undelivered[0].sender_id, "u1");
+
+        db.mark_delivered(&[id]).await.unwrap();
+        assert_eq!(db.fetch_undelivered("u2").await.unwrap().len(), 0);
+    }
+
+    /// Inserts a message with an explicit `created_at`, bypassing
+    /// [`Database::store_message`]'s use of [`Utc::now`], so tests can
+    /// simulate messages of arbitrary age.
+    async fn insert_message_at(db: &Database, recipient_id: &str, delivered: bool, created_at: DateTime<Utc>) {
+        sqlx::query(
+            "INSERT INTO messages (recipient_id, sender_id, message_number, nonce, ciphertext, signature, delivered, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(recipient_id)
+        .bind("u1")
+        .bind(0)
+        .bind("nonce")
+        .bind("cipher")
+        .bind("sig")
+        .bind(delivered as i64)
+        .bind(created_at.to_rfc3339())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn purge_expired_removes_only_messages_past_their_ttl() {
+        let db = Database::connect_with_retention(
+            "sqlite::memory:",
+            RetentionPolicy { delivered_ttl: Duration::days(1), undelivered_ttl: Duration::days(7) },
+        )
+        .await
+        .unwrap();
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+        db.register_user(new_user("u2", "bob")).await.unwrap();
+
+        let now = Utc::now();
+        insert_message_at(&db, "u2", true, now - Duration::days(2)).await; // expired, delivered
+        insert_message_at(&db, "u2", true, now - Duration::hours(1)).await; // fresh, delivered
+        insert_message_at(&db, "u2", false, now - Duration::days(10)).await; // expired, undelivered
+        insert_message_at(&db, "u2", false, now - Duration::days(1)).await; // fresh, undelivered
+
+        let purged = db.purge_expired(now).await.unwrap();
+        assert_eq!(purged, 2);
+
+        let remaining: i64 = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM messages").fetch_one(&db.pool).await.unwrap().0;
+        assert_eq!(remaining, 2);
+    }
+
+    #[tokio::test]
+    async fn running_migrations_twice_is_a_no_op_and_schema_migrations_records_every_version() {
+        let db = test_db().await;
+
+        let applied_before: Vec<(i64,)> = sqlx::query_as("SELECT version FROM schema_migrations ORDER BY version").fetch_all(&db.pool).await.unwrap();
+        assert_eq!(applied_before, MIGRATIONS.iter().map(|m| (m.version,)).collect::<Vec<_>>());
+
+        db.migrate().await.unwrap();
+
+        let applied_after: Vec<(i64,)> = sqlx::query_as("SELECT version FROM schema_migrations ORDER BY version").fetch_all(&db.pool).await.unwrap();
+        assert_eq!(applied_after, applied_before, "re-running migrations must not duplicate or re-apply already-applied versions");
+    }
+
+    #[tokio::test]
+    async fn get_public_user_finds_an_exact_match_and_reports_prekey_availability() {
+        let db = test_db().await;
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+
+        let before = db.get_public_user("alice").await.unwrap().unwrap();
+        assert_eq!(before, PublicUserInfo { id: "u1".to_string(), has_one_time_prekeys: false });
+
+        db.add_one_time_prekey("u1", "otk-1").await.unwrap();
+        let after = db.get_public_user("alice").await.unwrap().unwrap();
+        assert!(after.has_one_time_prekeys);
+
+        assert_eq!(db.get_public_user("nonexistent").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn nonexistent_username_still_runs_the_one_time_prekey_query_instead_of_short_circuiting() {
+        let db = test_db().await;
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+
+        // If a nonexistent username skipped the one_time_prekeys query
+        // (leaking existence through timing -- one query versus two),
+        // dropping that table wouldn't affect this call at all. With the
+        // query always running, it must now surface the table's absence
+        // as an error exactly like it would for an existing user.
+        sqlx::query("DROP TABLE one_time_prekeys").execute(&db.pool).await.unwrap();
+
+        assert!(db.get_public_user("alice").await.is_err());
+        assert!(db.get_public_user("nonexistent").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried_with_backoff_but_permanent_errors_are_not() {
+        let config = DatabaseConfig {
+            retry: RetryConfig { max_retries: 3, initial_backoff: StdDuration::from_millis(1), backoff_multiplier: 2.0 },
+            ..DatabaseConfig::default()
+        };
+        let db = Database::connect_with_config("sqlite::memory:", RetentionPolicy::default(), config).await.unwrap();
+
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let mut retries_seen = Vec::new();
+        let result = db
+            .with_retry_and_hook(
+                || async {
+                    if attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 2 {
+                        Err(DbError::Transient(sqlx::Error::PoolClosed))
+                    } else {
+                        Ok("eventually succeeded")
+                    }
+                },
+                &mut |attempt, delay| retries_seen.push((attempt, delay)),
+            )
+            .await;
+
+        assert_eq!(result.unwrap(), "eventually succeeded");
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(retries_seen, vec![(0, StdDuration::from_millis(1)), (1, StdDuration::from_millis(2))]);
+
+        let attempts = std::sync::atomic::AtomicU32::new(0);
+        let mut retries_seen = Vec::new();
+        let result: Result<(), DbError> = db
+            .with_retry_and_hook(
+                || async {
+                    attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Err(DbError::NotFound)
+                },
+                &mut |attempt, delay| retries_seen.push((attempt, delay)),
+            )
+            .await;
+
+        assert!(matches!(result, Err(DbError::NotFound)));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 1, "a permanent error must not be retried");
+        assert!(retries_seen.is_empty());
+    }
+
+    #[tokio::test]
+    async fn deleting_a_user_cascades_to_all_referencing_rows() {
+        let db = test_db().await;
+        db.register_user(new_user("u1", "alice")).await.unwrap();
+        db.register_user(new_user("u2", "bob")).await.unwrap();
+        db.add_one_time_prekey("u1", "otk-1").await.unwrap();
+        db.create_session("alice-token", "u1").await.unwrap();
+        db.store_message("u2", "u1", 0, "nonce", "cipher", "sig").await.unwrap();
+        db.store_message("u1", "u2", 0, "nonce", "cipher", "sig").await.unwrap();
+
+        db.delete_user("u1").await.unwrap();
+
+        assert!(db.find_user_by_username("alice").await.unwrap().is_none());
+        assert_eq!(db.session_user("alice-token").await.unwrap(), None);
+        assert_eq!(db.take_one_time_prekey("u1").await.unwrap(), None);
+
+        let remaining: i64 = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM messages WHERE sender_id = 'u1' OR recipient_id = 'u1'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap()
+            .0;
+        assert_eq!(remaining, 0);
+    }
+}
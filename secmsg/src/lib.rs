@@ -0,0 +1,19 @@
+//! A minimal end-to-end encrypted messaging service: X3DH key agreement
+//! plus a simplified Double Ratchet for the crypto (see [`crypto`]), and an
+//! axum/sqlx HTTP API that relays opaque ciphertext between registered
+//! users (see [`server`]).
+
+pub mod auth;
+pub mod client;
+pub mod crypto;
+pub mod db;
+pub mod server;
+
+pub use auth::{generate_session_token, hash_password, hash_password_with_config, verify_password, Argon2Config, AuthError};
+pub use client::{Bundle, Client, ClientError, IncomingMessage};
+pub use crypto::{
+    x3dh_initiate, x3dh_respond, verify_ratchet_message, CryptoError, IdentityKeyPair, PreKeyBundle, RatchetMessage, RatchetState, RootKey,
+    X3DHInitiation,
+};
+pub use db::{Database, DatabaseConfig, DbError, NewUser, PublicUserInfo, RetentionPolicy, RetryConfig, StoredMessage, UserRecord};
+pub use server::{build_router, ApiError, AppState, Metrics, RateLimitConfig};
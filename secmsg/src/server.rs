@@ -0,0 +1,785 @@
+//! The HTTP (and WebSocket) API: account registration/login, X3DH bundle
+//! exchange, and message send/receive. The server never sees plaintext or
+//! private key material -- every crypto field here is an opaque
+//! base64-encoded blob produced by [`crate::crypto`] on the client side.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use utoipa::{OpenApi, ToSchema};
+use uuid::Uuid;
+
+use crate::auth::{generate_session_token, hash_password_with_config, verify_password, Argon2Config};
+use crate::db::{Database, NewUser};
+
+/// An API-level error, carrying enough information to render a status
+/// code and a JSON `{ "error": ... }` body. Centralizes status mapping so
+/// handlers don't each pick their own `StatusCode`.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
+    Conflict(String),
+    Internal(String),
+    RateLimited(String),
+    PayloadTooLarge(String),
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::NotFound(message)
+            | ApiError::Conflict(message)
+            | ApiError::Internal(message)
+            | ApiError::RateLimited(message)
+            | ApiError::PayloadTooLarge(message) => message,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(ErrorBody { error: self.message() })).into_response()
+    }
+}
+
+/// Maximum sizes (after base64 decoding) accepted for a message's
+/// [`SendMessageRequest`] fields, enforced by [`send_message`] to keep a
+/// malicious sender from queueing arbitrarily large payloads.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageSizeLimits {
+    pub max_nonce_bytes: usize,
+    pub max_ciphertext_bytes: usize,
+}
+
+impl Default for MessageSizeLimits {
+    fn default() -> Self {
+        MessageSizeLimits { max_nonce_bytes: 64, max_ciphertext_bytes: 64 * 1024 }
+    }
+}
+
+/// How many calls to [`lookup`] a single caller may make per window, used
+/// to blunt username-enumeration attempts against the otherwise
+/// unauthenticated directory lookup.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    pub max_requests: u32,
+    pub window: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig { max_requests: 10, window: Duration::from_secs(60) }
+    }
+}
+
+/// A fixed-window rate limiter keyed by caller (e.g. IP address). Each
+/// key's count resets once its window has elapsed since the key's first
+/// request in the current window.
+#[derive(Debug)]
+struct RateLimiter {
+    config: RateLimitConfig,
+    windows: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+impl RateLimiter {
+    fn new(config: RateLimitConfig) -> Self {
+        RateLimiter { config, windows: Mutex::new(HashMap::new()) }
+    }
+
+    /// `true` if `key` still has room in its current window, in which case
+    /// this call counts against it. `false` if `key` has already used up
+    /// its window's quota.
+    ///
+    /// Opportunistically evicts every key whose window has expired before
+    /// inserting `key`'s entry, so `windows` can't grow without bound from
+    /// an unauthenticated, rate-limited-but-otherwise-unbounded endpoint
+    /// seeing one-off requests from distinct caller IPs.
+    fn check(&self, key: &str) -> bool {
+        let mut windows = self.windows.lock().expect("rate limiter mutex is never held across a panic");
+        let now = Instant::now();
+        windows.retain(|_, (_, started)| now.duration_since(*started) < self.config.window);
+        let entry = windows.entry(key.to_string()).or_insert((0, now));
+        if now.duration_since(entry.1) >= self.config.window {
+            *entry = (0, now);
+        }
+        if entry.0 >= self.config.max_requests {
+            return false;
+        }
+        entry.0 += 1;
+        true
+    }
+}
+
+/// Operational counters exposed via [`metrics`] in Prometheus text format.
+/// Wrapped in an `Arc` inside [`AppState`] so every clone of the state
+/// (one per request) increments the same counters.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub messages_sent: AtomicU64,
+    pub logins: AtomicU64,
+    pub failed_logins: AtomicU64,
+    pub rate_limit_rejections: AtomicU64,
+}
+
+impl Metrics {
+    fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help, value) in [
+            ("secmsg_messages_sent_total", "Total messages accepted by /messages.", self.messages_sent.load(Ordering::Relaxed)),
+            ("secmsg_logins_total", "Total successful logins.", self.logins.load(Ordering::Relaxed)),
+            ("secmsg_failed_logins_total", "Total login attempts rejected for a bad username or password.", self.failed_logins.load(Ordering::Relaxed)),
+            ("secmsg_rate_limit_rejections_total", "Total requests rejected for exceeding a rate limit.", self.rate_limit_rejections.load(Ordering::Relaxed)),
+        ] {
+            out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"));
+        }
+        out
+    }
+}
+
+/// Shared state handed to every handler: the database and a broadcast
+/// channel [`send_message`] publishes newly stored messages to, which
+/// [`ws_handler`] subscribes to for real-time delivery.
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Database,
+    broadcast: broadcast::Sender<(String, MessageOut)>,
+    argon2_config: Argon2Config,
+    message_size_limits: MessageSizeLimits,
+    metrics: Arc<Metrics>,
+    lookup_rate_limiter: Arc<RateLimiter>,
+}
+
+impl AppState {
+    pub fn new(db: Database) -> Self {
+        Self::new_with_argon2_config(db, Argon2Config::default())
+    }
+
+    pub fn new_with_argon2_config(db: Database, argon2_config: Argon2Config) -> Self {
+        let (broadcast, _) = broadcast::channel(256);
+        AppState {
+            db,
+            broadcast,
+            argon2_config,
+            message_size_limits: MessageSizeLimits::default(),
+            metrics: Arc::new(Metrics::default()),
+            lookup_rate_limiter: Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        }
+    }
+
+    pub fn with_message_size_limits(mut self, limits: MessageSizeLimits) -> Self {
+        self.message_size_limits = limits;
+        self
+    }
+
+    pub fn with_lookup_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.lookup_rate_limiter = Arc::new(RateLimiter::new(config));
+        self
+    }
+}
+
+/// OpenAPI spec for the handlers below, served as JSON by [`openapi_spec`]
+/// at `GET /openapi.json` so other teams can generate clients without
+/// reading this file. Each listed handler carries a `#[utoipa::path]`
+/// annotation describing its request/response shape; each listed schema is
+/// one of the `Serialize`/`Deserialize` request or response types those
+/// handlers use.
+#[derive(OpenApi)]
+#[openapi(
+    paths(register, login, send_message),
+    components(schemas(RegisterRequest, RegisterResponse, LoginRequest, LoginResponse, SendMessageRequest, MessageOut))
+)]
+struct ApiDoc;
+
+/// Machine-readable description of this API's request/response shapes,
+/// generated from the `#[utoipa::path]`/`#[derive(ToSchema)]` annotations
+/// on the handlers and types in this module.
+async fn openapi_spec() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+pub fn build_router(state: AppState) -> Router {
+    Router::new()
+        .route("/register", axum::routing::post(register))
+        .route("/login", axum::routing::post(login))
+        .route("/bundle/:username", get(fetch_bundle))
+        .route("/lookup", axum::routing::post(lookup))
+        .route("/messages", axum::routing::post(send_message).get(get_messages))
+        .route("/account", axum::routing::delete(delete_account))
+        .route("/metrics", get(metrics))
+        .route("/openapi.json", get(openapi_spec))
+        .route("/ws", get(ws_handler))
+        .with_state(state)
+}
+
+/// Render operational counters in Prometheus text exposition format.
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    (StatusCode::OK, [("content-type", "text/plain; version=0.0.4")], state.metrics.render())
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RegisterRequest {
+    username: String,
+    password: String,
+    identity_key: String,
+    identity_dh: String,
+    signed_prekey: String,
+    signed_prekey_signature: String,
+    #[serde(default)]
+    one_time_prekeys: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct RegisterResponse {
+    user_id: String,
+}
+
+#[utoipa::path(post, path = "/register", request_body = RegisterRequest, responses((status = OK, body = RegisterResponse)))]
+async fn register(State(state): State<AppState>, Json(req): Json<RegisterRequest>) -> Result<Json<RegisterResponse>, ApiError> {
+    let user_id = Uuid::new_v4().to_string();
+    let password_hash = hash_password_with_config(&req.password, state.argon2_config).map_err(|err| ApiError::Internal(err.to_string()))?;
+
+    state
+        .db
+        .register_user(NewUser {
+            id: &user_id,
+            username: &req.username,
+            password_hash: &password_hash,
+            identity_key: &req.identity_key,
+            identity_dh: &req.identity_dh,
+            signed_prekey: &req.signed_prekey,
+            signed_prekey_signature: &req.signed_prekey_signature,
+        })
+        .await
+        .map_err(|err| ApiError::Conflict(err.to_string()))?;
+
+    for prekey in &req.one_time_prekeys {
+        state.db.add_one_time_prekey(&user_id, prekey).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+    }
+
+    Ok(Json(RegisterResponse { user_id }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct LoginResponse {
+    token: String,
+    user_id: String,
+}
+
+#[utoipa::path(post, path = "/login", request_body = LoginRequest, responses((status = OK, body = LoginResponse)))]
+async fn login(State(state): State<AppState>, Json(req): Json<LoginRequest>) -> Result<Json<LoginResponse>, ApiError> {
+    let user = match state.db.find_user_by_username(&req.username).await.map_err(|err| ApiError::Internal(err.to_string()))? {
+        Some(user) => user,
+        None => {
+            state.metrics.failed_logins.fetch_add(1, Ordering::Relaxed);
+            return Err(ApiError::Unauthorized("invalid username or password".to_string()));
+        }
+    };
+
+    if !verify_password(&user.password_hash, &req.password).map_err(|err| ApiError::Internal(err.to_string()))? {
+        state.metrics.failed_logins.fetch_add(1, Ordering::Relaxed);
+        return Err(ApiError::Unauthorized("invalid username or password".to_string()));
+    }
+
+    let token = generate_session_token();
+    state.db.create_session(&token, &user.id).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+    state.metrics.logins.fetch_add(1, Ordering::Relaxed);
+
+    Ok(Json(LoginResponse { token, user_id: user.id }))
+}
+
+#[derive(Debug, Serialize)]
+struct BundleResponse {
+    identity_key: String,
+    identity_dh: String,
+    signed_prekey: String,
+    signed_prekey_signature: String,
+    one_time_prekey: Option<String>,
+}
+
+async fn fetch_bundle(
+    State(state): State<AppState>,
+    axum::extract::Path(username): axum::extract::Path<String>,
+) -> Result<Json<BundleResponse>, ApiError> {
+    let user = state
+        .db
+        .find_user_by_username(&username)
+        .await
+        .map_err(|err| ApiError::Internal(err.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("unknown user".to_string()))?;
+
+    let one_time_prekey = state.db.take_one_time_prekey(&user.id).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+
+    Ok(Json(BundleResponse {
+        identity_key: user.identity_key,
+        identity_dh: user.identity_dh,
+        signed_prekey: user.signed_prekey,
+        signed_prekey_signature: user.signed_prekey_signature,
+        one_time_prekey,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct LookupRequest {
+    username: String,
+}
+
+#[derive(Debug, Serialize)]
+struct LookupResponse {
+    user_id: String,
+    has_prekeys: bool,
+}
+
+/// Directory lookup: resolve a username to its public id and prekey
+/// availability, for an exact match only -- no wildcard or prefix search,
+/// so a caller can't enumerate the user base one character at a time.
+/// Rate-limited per caller since it needs no authentication.
+async fn lookup(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(req): Json<LookupRequest>,
+) -> Result<Json<LookupResponse>, ApiError> {
+    if !state.lookup_rate_limiter.check(&addr.ip().to_string()) {
+        state.metrics.rate_limit_rejections.fetch_add(1, Ordering::Relaxed);
+        return Err(ApiError::RateLimited("too many lookup requests".to_string()));
+    }
+
+    let user = state.db.get_public_user(&req.username).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+    let user = user.ok_or_else(|| ApiError::NotFound("unknown user".to_string()))?;
+
+    Ok(Json(LookupResponse { user_id: user.id, has_prekeys: user.has_one_time_prekeys }))
+}
+
+async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<String, ApiError> {
+    let token = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| ApiError::Unauthorized("missing bearer token".to_string()))?;
+
+    state
+        .db
+        .session_user(token)
+        .await
+        .map_err(|err| ApiError::Internal(err.to_string()))?
+        .ok_or_else(|| ApiError::Unauthorized("invalid session token".to_string()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct SendMessageRequest {
+    recipient_id: String,
+    message_number: i64,
+    nonce: String,
+    ciphertext: String,
+    signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MessageOut {
+    pub id: i64,
+    pub sender_id: String,
+    pub message_number: i64,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub signature: String,
+}
+
+/// Decode `field`'s base64 content and check it against `max_bytes`,
+/// returning 413 if it's too large or 400 if it isn't valid base64.
+fn decode_within_limit(field_name: &str, field: &str, max_bytes: usize) -> Result<Vec<u8>, ApiError> {
+    let bytes = decode(field).map_err(|err| ApiError::BadRequest(format!("{field_name} is not valid base64: {err}")))?;
+    if bytes.len() > max_bytes {
+        return Err(ApiError::PayloadTooLarge(format!("{field_name} is {} bytes, exceeding the limit of {max_bytes}", bytes.len())));
+    }
+    Ok(bytes)
+}
+
+/// This is the API's message-send endpoint, routed at `POST /messages`
+/// rather than `/send`.
+#[utoipa::path(post, path = "/messages", request_body = SendMessageRequest, responses((status = CREATED)))]
+async fn send_message(State(state): State<AppState>, headers: HeaderMap, Json(req): Json<SendMessageRequest>) -> Result<StatusCode, ApiError> {
+    let sender_id = authenticate(&state, &headers).await?;
+
+    decode_within_limit("nonce", &req.nonce, state.message_size_limits.max_nonce_bytes)?;
+    decode_within_limit("ciphertext", &req.ciphertext, state.message_size_limits.max_ciphertext_bytes)?;
+    let signature = decode_within_limit("signature", &req.signature, ed25519_dalek::SIGNATURE_LENGTH)?;
+    if signature.len() != ed25519_dalek::SIGNATURE_LENGTH {
+        return Err(ApiError::BadRequest(format!("signature must be exactly {} bytes, got {}", ed25519_dalek::SIGNATURE_LENGTH, signature.len())));
+    }
+
+    let id = state
+        .db
+        .store_message(&req.recipient_id, &sender_id, req.message_number, &req.nonce, &req.ciphertext, &req.signature)
+        .await
+        .map_err(|err| ApiError::Internal(err.to_string()))?;
+
+    let out = MessageOut { id, sender_id, message_number: req.message_number, nonce: req.nonce, ciphertext: req.ciphertext, signature: req.signature };
+    let _ = state.broadcast.send((req.recipient_id, out));
+    state.metrics.messages_sent.fetch_add(1, Ordering::Relaxed);
+
+    Ok(StatusCode::CREATED)
+}
+
+#[derive(Debug, Serialize)]
+struct GetMessagesResponse {
+    messages: Vec<MessageOut>,
+}
+
+async fn get_messages(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<GetMessagesResponse>, ApiError> {
+    let user_id = authenticate(&state, &headers).await?;
+
+    let stored = state.db.fetch_undelivered(&user_id).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+    let ids: Vec<i64> = stored.iter().map(|m| m.id).collect();
+    state.db.mark_delivered(&ids).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+
+    let messages = stored
+        .into_iter()
+        .map(|m| MessageOut { id: m.id, sender_id: m.sender_id, message_number: m.message_number, nonce: m.nonce, ciphertext: m.ciphertext, signature: m.signature })
+        .collect();
+    Ok(Json(GetMessagesResponse { messages }))
+}
+
+async fn delete_account(State(state): State<AppState>, headers: HeaderMap) -> Result<StatusCode, ApiError> {
+    let user_id = authenticate(&state, &headers).await?;
+    state.db.delete_user(&user_id).await.map_err(|err| ApiError::Internal(err.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct WsParams {
+    token: String,
+}
+
+async fn ws_handler(State(state): State<AppState>, Query(params): Query<WsParams>, ws: WebSocketUpgrade) -> impl IntoResponse {
+    match state.db.session_user(&params.token).await {
+        Ok(Some(user_id)) => ws.on_upgrade(move |socket| push_messages(socket, state, user_id)).into_response(),
+        Ok(None) => ApiError::Unauthorized("invalid session token".to_string()).into_response(),
+        Err(err) => ApiError::Internal(err.to_string()).into_response(),
+    }
+}
+
+async fn push_messages(mut socket: WebSocket, state: AppState, user_id: String) {
+    let mut receiver = state.broadcast.subscribe();
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                match incoming {
+                    Some(Ok(WsMessage::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(_)) => break,
+                }
+            }
+            published = receiver.recv() => {
+                let Ok((recipient_id, message)) = published else { break };
+                if recipient_id != user_id {
+                    continue;
+                }
+                let Ok(text) = serde_json::to_string(&message) else { continue };
+                if socket.send(WsMessage::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn encode(bytes: &[u8]) -> String {
+    BASE64.encode(bytes)
+}
+
+fn decode(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    BASE64.decode(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn test_state() -> AppState {
+        AppState::new(Database::connect("sqlite::memory:").await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn openapi_spec_describes_register_login_and_send_with_their_request_schemas() {
+        let app = build_router(test_state().await);
+
+        let response = app
+            .oneshot(Request::builder().method("GET").uri("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let paths = &spec["paths"];
+
+        assert!(paths.get("/register").is_some(), "spec is missing /register: {spec}");
+        assert!(paths.get("/login").is_some(), "spec is missing /login: {spec}");
+        // This API's message-send endpoint is routed at `/messages`, not
+        // `/send` -- there's no literal `/send` path to describe.
+        assert!(paths.get("/messages").is_some(), "spec is missing /messages: {spec}");
+
+        let schemas = &spec["components"]["schemas"];
+        assert!(schemas.get("RegisterRequest").is_some(), "spec is missing the RegisterRequest schema: {spec}");
+        assert!(schemas.get("LoginRequest").is_some(), "spec is missing the LoginRequest schema: {spec}");
+        assert!(schemas.get("SendMessageRequest").is_some(), "spec is missing the SendMessageRequest schema: {spec}");
+    }
+
+    #[tokio::test]
+    async fn api_error_not_found_serializes_to_a_404_with_a_json_error_body() {
+        let response = ApiError::NotFound("unknown user".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(parsed, serde_json::json!({ "error": "unknown user" }));
+    }
+
+    fn register_body(username: &str) -> String {
+        serde_json::json!({
+            "username": username,
+            "password": "hunter2",
+            "identity_key": "aWs=",
+            "identity_dh": "aWRo",
+            "signed_prekey": "c3Br",
+            "signed_prekey_signature": "c2lnbg==",
+            "one_time_prekeys": []
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn register_then_login_round_trip_issues_a_usable_token() {
+        let app = build_router(test_state().await);
+
+        let register_response = app
+            .clone()
+            .oneshot(Request::builder().method("POST").uri("/register").header("content-type", "application/json").body(Body::from(register_body("alice"))).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(register_response.status(), StatusCode::OK);
+
+        let login_body = serde_json::json!({ "username": "alice", "password": "hunter2" }).to_string();
+        let login_response = app
+            .oneshot(Request::builder().method("POST").uri("/login").header("content-type", "application/json").body(Body::from(login_body)).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(login_response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn connected_client_receives_a_message_published_after_connection() {
+        let state = test_state().await;
+        state
+            .db
+            .register_user(NewUser { id: "u2", username: "bob", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        state.db.create_session("bob-token", "u2").await.unwrap();
+
+        let mut receiver = state.broadcast.subscribe();
+        let out = MessageOut { id: 1, sender_id: "u1".to_string(), message_number: 0, nonce: "n".to_string(), ciphertext: "c".to_string(), signature: "s".to_string() };
+        state.broadcast.send(("u2".to_string(), out.clone())).unwrap();
+
+        let (recipient, received) = receiver.recv().await.unwrap();
+        assert_eq!(recipient, "u2");
+        assert_eq!(received.ciphertext, "c");
+    }
+
+    #[tokio::test]
+    async fn deleting_account_revokes_its_session_token() {
+        let state = test_state().await;
+        state
+            .db
+            .register_user(NewUser { id: "u1", username: "alice", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        state.db.create_session("alice-token", "u1").await.unwrap();
+        let app = build_router(state);
+
+        let response = app
+            .oneshot(Request::builder().method("DELETE").uri("/account").header("authorization", "Bearer alice-token").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    async fn send_message_body(ciphertext_len: usize) -> String {
+        let signature = encode(&[0u8; ed25519_dalek::SIGNATURE_LENGTH]);
+        let ciphertext = encode(&vec![0u8; ciphertext_len]);
+        serde_json::json!({
+            "recipient_id": "u2",
+            "message_number": 0,
+            "nonce": encode(&[0u8; 12]),
+            "ciphertext": ciphertext,
+            "signature": signature,
+        })
+        .to_string()
+    }
+
+    async fn send_message_request(token: &str, body: String) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/messages")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {token}"))
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn oversized_message_content_is_rejected_but_normal_content_is_accepted() {
+        let state = test_state().await;
+        state
+            .db
+            .register_user(NewUser { id: "u1", username: "alice", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        state
+            .db
+            .register_user(NewUser { id: "u2", username: "bob", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        state.db.create_session("alice-token", "u1").await.unwrap();
+        let app = build_router(state);
+
+        let oversized = send_message_request("alice-token", send_message_body(MessageSizeLimits::default().max_ciphertext_bytes + 1).await).await;
+        let response = app.clone().oneshot(oversized).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let normal = send_message_request("alice-token", send_message_body(256).await).await;
+        let response = app.oneshot(normal).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    fn lookup_request(username: &str, addr: SocketAddr) -> Request<Body> {
+        let body = serde_json::json!({ "username": username }).to_string();
+        let mut request = Request::builder().method("POST").uri("/lookup").header("content-type", "application/json").body(Body::from(body)).unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+        request
+    }
+
+    #[tokio::test]
+    async fn exact_username_match_returns_public_info_but_unknown_username_404s() {
+        let state = test_state().await;
+        state
+            .db
+            .register_user(NewUser { id: "u1", username: "alice", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        let app = build_router(state);
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+
+        let found = app.clone().oneshot(lookup_request("alice", addr)).await.unwrap();
+        assert_eq!(found.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(found.into_body(), usize::MAX).await.unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(parsed["user_id"], "u1");
+        assert_eq!(parsed["has_prekeys"], false);
+
+        let missing = app.oneshot(lookup_request("nonexistent", addr)).await.unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn lookup_requests_beyond_the_per_caller_limit_are_rate_limited() {
+        let state = test_state().await.with_lookup_rate_limit(RateLimitConfig { max_requests: 2, window: Duration::from_secs(60) });
+        state
+            .db
+            .register_user(NewUser { id: "u1", username: "alice", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        let app = build_router(state);
+        let addr: SocketAddr = "127.0.0.1:1234".parse().unwrap();
+
+        for _ in 0..2 {
+            let response = app.clone().oneshot(lookup_request("alice", addr)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let limited = app.oneshot(lookup_request("alice", addr)).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn sending_a_message_increments_the_messages_sent_counter_reported_at_metrics() {
+        let state = test_state().await;
+        state
+            .db
+            .register_user(NewUser { id: "u1", username: "alice", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        state
+            .db
+            .register_user(NewUser { id: "u2", username: "bob", password_hash: "hash", identity_key: "ik", identity_dh: "idh", signed_prekey: "spk", signed_prekey_signature: "sig" })
+            .await
+            .unwrap();
+        state.db.create_session("alice-token", "u1").await.unwrap();
+        let app = build_router(state);
+
+        let send_request = send_message_request("alice-token", send_message_body(256).await).await;
+        let send_response = app.clone().oneshot(send_request).await.unwrap();
+        assert_eq!(send_response.status(), StatusCode::CREATED);
+
+        let metrics_response = app.oneshot(Request::builder().method("GET").uri("/metrics").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(metrics_response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(metrics_response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("secmsg_messages_sent_total 1"), "unexpected metrics body: {text}");
+    }
+
+    #[test]
+    fn stale_rate_limiter_windows_are_evicted_instead_of_accumulating_forever() {
+        let limiter = RateLimiter::new(RateLimitConfig { max_requests: 1, window: Duration::from_millis(10) });
+        limiter.check("first-caller");
+        std::thread::sleep(Duration::from_millis(20));
+        limiter.check("second-caller");
+
+        let windows = limiter.windows.lock().unwrap();
+        assert_eq!(windows.len(), 1, "first-caller's expired window should have been evicted rather than kept around alongside second-caller's");
+        assert!(windows.contains_key("second-caller"));
+    }
+}
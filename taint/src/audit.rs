@@ -0,0 +1,94 @@
+//! First-class audit trails for declassification, instead of callers
+//! reimplementing logging around every `declassify()` call.
+
+use std::fmt;
+
+/// A single recorded declassification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub reason: String,
+    pub type_name: &'static str,
+}
+
+/// Records declassification events. Implement this to forward entries to a
+/// real audit log (syslog, a database, ...); [`InMemoryAudit`] is a default
+/// in-process implementation good enough for tests and small tools.
+pub trait DeclassifyAudit {
+    fn record(&mut self, entry: AuditEntry);
+}
+
+/// An in-memory [`DeclassifyAudit`] that just accumulates entries.
+#[derive(Debug, Default)]
+pub struct InMemoryAudit {
+    entries: Vec<AuditEntry>,
+}
+
+impl InMemoryAudit {
+    pub fn new() -> Self {
+        InMemoryAudit::default()
+    }
+
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+}
+
+impl DeclassifyAudit for InMemoryAudit {
+    fn record(&mut self, mut entry: AuditEntry) {
+        entry.sequence = self.entries.len() as u64;
+        self.entries.push(entry);
+    }
+}
+
+/// Error from a [`DeclassGuard`] whose declassification budget has been
+/// exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeclassBudgetExceeded {
+    pub limit: usize,
+}
+
+impl fmt::Display for DeclassBudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "declassification budget of {} exceeded", self.limit)
+    }
+}
+
+impl std::error::Error for DeclassBudgetExceeded {}
+
+/// Caps how many declassifications may happen while this guard is alive,
+/// for catching accidental mass-declassification in an audit-heavy scope.
+/// Pass one to [`crate::Tainted::declassify_guarded`] at each call site that
+/// should count against the budget.
+#[derive(Debug, Clone)]
+pub struct DeclassGuard {
+    limit: usize,
+    count: usize,
+}
+
+impl DeclassGuard {
+    /// Allow up to `limit` declassifications before
+    /// [`DeclassGuard::consume`] starts erroring.
+    pub fn new(limit: usize) -> Self {
+        DeclassGuard { limit, count: 0 }
+    }
+
+    /// Count one more declassification against the budget, erroring
+    /// instead of declassifying once `limit` has already been reached.
+    pub fn consume(&mut self) -> Result<(), DeclassBudgetExceeded> {
+        if self.count >= self.limit {
+            return Err(DeclassBudgetExceeded { limit: self.limit });
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Declassifications counted so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
@@ -0,0 +1,80 @@
+use crate::level::{Public, Secret, TaintLevel};
+use crate::tainted::Tainted;
+
+/// Apply `f` to each element of a collection of same-level tainted values,
+/// preserving the taint level.
+pub fn map_collection<T, U, L: TaintLevel>(
+    items: Vec<Tainted<T, L>>,
+    mut f: impl FnMut(T) -> U,
+) -> Vec<Tainted<U, L>> {
+    items.into_iter().map(|item| item.map(&mut f)).collect()
+}
+
+/// A `Vec<T>` tainted as a whole: mixing in even one secret element taints
+/// the entire collection, and secret elements can only be read through
+/// [`TaintedVec::iter_exposed`] or [`TaintedVec::declassify_all`].
+pub struct TaintedVec<T, L: TaintLevel> {
+    items: Vec<T>,
+    _level: std::marker::PhantomData<L>,
+}
+
+impl<T, L: TaintLevel> TaintedVec<T, L> {
+    pub fn new() -> Self {
+        TaintedVec { items: Vec::new(), _level: std::marker::PhantomData }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Add an element, which inherits the collection's taint level.
+    pub fn push(&mut self, value: T) {
+        self.items.push(value);
+    }
+}
+
+impl<T, L: TaintLevel> Default for TaintedVec<T, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TaintedVec<T, Secret> {
+    /// Controlled access to the secret elements: the closure can look, but
+    /// nothing escapes as a bare (un-tainted) value.
+    pub fn iter_exposed(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    /// Declassify every element, handing back a `Public` vec.
+    pub fn declassify_all(self) -> TaintedVec<T, Public> {
+        TaintedVec { items: self.items, _level: std::marker::PhantomData }
+    }
+}
+
+impl<T> TaintedVec<T, Public> {
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_vec_exposes_only_through_accessor() {
+        let mut v: TaintedVec<String, Secret> = TaintedVec::new();
+        v.push("a".to_string());
+        v.push("b".to_string());
+        let collected: Vec<&String> = v.iter_exposed().collect();
+        assert_eq!(collected, vec!["a", "b"]);
+
+        let declassified = v.declassify_all();
+        assert_eq!(declassified.as_slice(), &["a".to_string(), "b".to_string()]);
+    }
+}
@@ -0,0 +1,85 @@
+//! Taint level markers.
+//!
+//! `TaintLevel` is sealed so downstream crates cannot invent new levels that
+//! would bypass the lattice rules baked into [`crate::Tainted`]. Levels form
+//! a total order `Public < Confidential < Secret`; combining two tainted
+//! values always resolves to the more restrictive of the two.
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A compile-time marker for how sensitive a [`crate::Tainted`] value is.
+pub trait TaintLevel: sealed::Sealed + ConfidentialCombineImpl + Clone + Copy + Default {
+    /// Human readable name, used in redacted `Debug` output.
+    const NAME: &'static str;
+
+    /// Position in the `Public < Confidential < Secret` lattice; higher is
+    /// more restrictive.
+    fn level_rank() -> u8;
+
+    /// The level two values combine to: the more restrictive of `Self` and `Other`.
+    type Combined<Other: TaintLevel>: TaintLevel;
+}
+
+/// Data that must never be exposed to a public sink without declassification.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Secret;
+
+/// Data that is internal-only: more sensitive than `Public`, but not
+/// top-secret. Sits between `Public` and `Secret` in the lattice.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Confidential;
+
+/// Data that is safe to log, serialize, or send to external sinks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Public;
+
+impl sealed::Sealed for Secret {}
+impl sealed::Sealed for Confidential {}
+impl sealed::Sealed for Public {}
+
+impl TaintLevel for Secret {
+    const NAME: &'static str = "Secret";
+    fn level_rank() -> u8 {
+        2
+    }
+    type Combined<Other: TaintLevel> = Secret;
+}
+
+impl TaintLevel for Confidential {
+    const NAME: &'static str = "Confidential";
+    fn level_rank() -> u8 {
+        1
+    }
+    type Combined<Other: TaintLevel> = ConfidentialCombine<Other>;
+}
+
+impl TaintLevel for Public {
+    const NAME: &'static str = "Public";
+    fn level_rank() -> u8 {
+        0
+    }
+    type Combined<Other: TaintLevel> = Other;
+}
+
+/// Helper mapping `Confidential` combined with `Other` to the more
+/// restrictive of the two (`Public` stays `Confidential`, `Secret` wins).
+pub type ConfidentialCombine<Other> = <Other as ConfidentialCombineImpl>::Output;
+
+#[doc(hidden)]
+pub trait ConfidentialCombineImpl {
+    type Output: TaintLevel;
+}
+
+impl ConfidentialCombineImpl for Public {
+    type Output = Confidential;
+}
+
+impl ConfidentialCombineImpl for Confidential {
+    type Output = Confidential;
+}
+
+impl ConfidentialCombineImpl for Secret {
+    type Output = Secret;
+}
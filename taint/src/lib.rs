@@ -0,0 +1,32 @@
+//! Compile-time taint tracking for secret/public data flow.
+//!
+//! [`Tainted<T, L>`] wraps a value with a taint level marker (`Secret` or
+//! `Public`). Secret values can't be displayed, logged, or serialized; the
+//! only way to turn one into a public value is an explicit
+//! [`Tainted::declassify`] call, which keeps data-flow leaks greppable.
+
+mod audit;
+mod collection;
+mod level;
+mod sanitizer;
+mod secret_string;
+mod sink;
+mod tainted;
+
+pub use audit::{AuditEntry, DeclassBudgetExceeded, DeclassGuard, DeclassifyAudit, InMemoryAudit};
+pub use collection::{map_collection, TaintedVec};
+pub use level::{Confidential, Public, Secret, TaintLevel};
+pub use sanitizer::Sanitizer;
+pub use secret_string::SecretString;
+pub use sink::{Logger, NetworkSink, RejectDigitSequences, SanitizingSink, SinkPolicy, TaintedSink};
+#[cfg(feature = "tracing")]
+pub use sink::TracingSink;
+pub use tainted::{CheckedAdd, Tainted};
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+#[cfg(feature = "zeroize")]
+mod zeroize_support;
+#[cfg(feature = "zeroize")]
+pub use zeroize_support::SecretZ;
@@ -0,0 +1,78 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::level::{Public, Secret};
+use crate::tainted::Tainted;
+
+/// Helpers for turning `Secret` values into `Public` ones without exposing
+/// the raw contents.
+pub struct Sanitizer;
+
+impl Sanitizer {
+    /// Fingerprint a secret string with `DefaultHasher`, a fast
+    /// non-cryptographic hash meant for hash-map bucketing, not for
+    /// fingerprinting secrets: it isn't collision-resistant, has no salt,
+    /// and its 64-bit output is brute-forceable. Only reach for this when
+    /// you need a cheap, throwaway comparison key and the `crypto` feature
+    /// isn't available; otherwise prefer [`Sanitizer::hash_secret_sha256`],
+    /// which is safe to log, persist, or compare across runs.
+    pub fn hash_secret(secret: &Tainted<String, Secret>) -> Tainted<u64, Public> {
+        let mut hasher = DefaultHasher::new();
+        secret.expose_secret().hash(&mut hasher);
+        Tainted::public(hasher.finish())
+    }
+
+    /// Fingerprint a secret string with SHA-256, returning a hex digest.
+    ///
+    /// Unlike [`Sanitizer::hash_secret`] (which uses the non-cryptographic
+    /// `DefaultHasher` and is only suitable for hash-map bucketing), this is
+    /// safe to use as a comparable, non-reversible fingerprint. Pass a
+    /// per-deployment `salt` to prevent rainbow-table lookups of short
+    /// secrets.
+    #[cfg(feature = "crypto")]
+    pub fn hash_secret_sha256(secret: &Tainted<String, Secret>, salt: &str) -> Tainted<String, Public> {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update(secret.expose_secret().as_bytes());
+        let digest = hasher.finalize();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        Tainted::public(hex)
+    }
+
+    /// Mask all but the first `show` characters of a secret string, e.g.
+    /// `"hunter2"` with `show = 2` becomes `"hu*****"`.
+    ///
+    /// Operates on `char` boundaries (not byte indices), so multi-byte
+    /// UTF-8 secrets (accents, emoji, ...) never cause a panic.
+    pub fn mask(secret: &Tainted<String, Secret>, show: usize) -> Tainted<String, Public> {
+        let s = secret.expose_secret();
+        let char_count = s.chars().count();
+        let show = show.min(char_count);
+        let visible: String = s.chars().take(show).collect();
+        let hidden_len = char_count - show;
+        Tainted::public(format!("{}{}", visible, "*".repeat(hidden_len)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_does_not_panic_on_multibyte_chars() {
+        let secret = Tainted::secret("café🦀123456".to_string());
+        let masked = Sanitizer::mask(&secret, 4);
+        assert_eq!(masked.expose(), "café*******");
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn different_salts_produce_different_fingerprints() {
+        let secret = Tainted::secret("hunter2".to_string());
+        let a = Sanitizer::hash_secret_sha256(&secret, "salt-a");
+        let b = Sanitizer::hash_secret_sha256(&secret, "salt-b");
+        assert_ne!(a.expose(), b.expose());
+    }
+}
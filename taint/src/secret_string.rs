@@ -0,0 +1,61 @@
+use std::env::VarError;
+
+use crate::level::{Public, Secret};
+use crate::tainted::Tainted;
+
+/// `Tainted<String, Secret>` under a friendlier name for the common case of
+/// wrapping a `String`.
+pub type SecretString = Tainted<String, Secret>;
+
+impl Tainted<String, Secret> {
+    /// Wrap a borrowed string as secret.
+    pub fn from_borrowed(s: &str) -> Self {
+        Tainted::secret(s.to_string())
+    }
+
+    /// Read an environment variable and wrap it as secret. The value is
+    /// never logged or displayed along the way.
+    pub fn from_env(var: &str) -> Result<Self, VarError> {
+        std::env::var(var).map(Tainted::secret)
+    }
+
+    /// Like [`Tainted::from_env`], but falls back to `default` (also
+    /// wrapped as secret) instead of erroring if `var` is unset or not
+    /// valid Unicode.
+    pub fn from_env_or(var: &str, default: impl Into<String>) -> Self {
+        Self::from_env(var).unwrap_or_else(|_| Tainted::secret(default.into()))
+    }
+
+    /// Public length of the secret string, without exposing its contents.
+    pub fn len_public(&self) -> Tainted<usize, Public> {
+        Tainted::public(self.expose_secret().len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_env_reads_and_preserves_length() {
+        std::env::set_var("TAINT_TEST_SECRET", "hunter2");
+        let secret = SecretString::from_env("TAINT_TEST_SECRET").unwrap();
+        assert_eq!(*secret.len_public().expose(), 7);
+        std::env::remove_var("TAINT_TEST_SECRET");
+    }
+
+    #[test]
+    fn from_env_or_falls_back_to_the_default_when_unset() {
+        std::env::remove_var("TAINT_TEST_SECRET_MISSING");
+        let secret = SecretString::from_env_or("TAINT_TEST_SECRET_MISSING", "fallback");
+        assert_eq!(secret.expose_secret(), "fallback");
+    }
+
+    #[test]
+    fn from_env_or_prefers_the_set_value_over_the_default() {
+        std::env::set_var("TAINT_TEST_SECRET_PRESENT", "hunter2");
+        let secret = SecretString::from_env_or("TAINT_TEST_SECRET_PRESENT", "fallback");
+        assert_eq!(secret.expose_secret(), "hunter2");
+        std::env::remove_var("TAINT_TEST_SECRET_PRESENT");
+    }
+}
@@ -0,0 +1,51 @@
+//! `Serialize`/`Deserialize` for `Tainted<T, Public>` only.
+//!
+//! `Tainted<T, Secret>` deliberately has no such impl: serializing a secret
+//! would be exactly the kind of accidental leak this crate exists to
+//! prevent, so the only way to get serializable data is to `declassify`
+//! it first.
+//!
+//! ```compile_fail
+//! # use taint::{Tainted, Secret};
+//! let secret: Tainted<u32, Secret> = Tainted::secret(1);
+//! serde_json::to_string(&secret).unwrap(); // Secret has no Serialize impl
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::level::Public;
+use crate::tainted::Tainted;
+
+impl<T: Serialize> Serialize for Tainted<T, Public> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.expose().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tainted<T, Public> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Tainted::public)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_round_trips_through_json() {
+        let value: Tainted<u32, Public> = Tainted::public(42);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "42");
+        let back: Tainted<u32, Public> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*back.expose(), 42);
+    }
+
+    // `Tainted<u32, Secret>` has no `Serialize` impl at all, so the following
+    // would fail to compile if uncommented:
+    //
+    // ```compile_fail
+    // let secret: taint::Tainted<u32, taint::Secret> = taint::Tainted::secret(1);
+    // serde_json::to_string(&secret).unwrap();
+    // ```
+}
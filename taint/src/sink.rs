@@ -0,0 +1,214 @@
+use std::fmt::Display;
+
+use crate::level::{Public, Secret};
+use crate::tainted::Tainted;
+
+/// A destination that only ever accepts `Public` data, so it can't leak
+/// secrets. Implement this instead of hand-rolling a `log`/`send` method so
+/// new sinks (files, syslog, ...) plug into [`SanitizingSink`] uniformly.
+pub trait TaintedSink {
+    fn accept<T: Display>(&mut self, value: &Tainted<T, Public>);
+}
+
+/// A check sinks can run on `Public` data before accepting it, for catching
+/// public-but-still-sensitive values (e.g. a taint-`Public` email address
+/// that's still PII) that the taint level alone doesn't flag.
+pub trait SinkPolicy {
+    fn allow<T: Display>(&self, value: &Tainted<T, Public>) -> bool;
+}
+
+/// A [`SinkPolicy`] that rejects any value whose rendered form contains a
+/// run of at least `min_digits` consecutive digits, e.g. to catch
+/// card-like numbers.
+pub struct RejectDigitSequences {
+    pub min_digits: usize,
+}
+
+impl SinkPolicy for RejectDigitSequences {
+    fn allow<T: Display>(&self, value: &Tainted<T, Public>) -> bool {
+        let mut run = 0;
+        for ch in value.to_string().chars() {
+            if ch.is_ascii_digit() {
+                run += 1;
+                if run >= self.min_digits {
+                    return false;
+                }
+            } else {
+                run = 0;
+            }
+        }
+        true
+    }
+}
+
+/// Emit `value` as a tracing event at `level`, on the `"taint"` target.
+/// `tracing::event!` needs its level as a token known at macro-expansion
+/// time, so a runtime `Level` has to be dispatched by hand instead of
+/// passed straight through.
+#[cfg(feature = "tracing")]
+fn emit_at<T: Display>(level: tracing::Level, value: &Tainted<T, Public>) {
+    match level {
+        tracing::Level::ERROR => tracing::error!(target: "taint", "{}", value),
+        tracing::Level::WARN => tracing::warn!(target: "taint", "{}", value),
+        tracing::Level::INFO => tracing::info!(target: "taint", "{}", value),
+        tracing::Level::DEBUG => tracing::debug!(target: "taint", "{}", value),
+        tracing::Level::TRACE => tracing::trace!(target: "taint", "{}", value),
+    }
+}
+
+/// A sink that only ever receives `Public` data, so it can't leak secrets.
+pub struct Logger;
+
+impl Logger {
+    /// Log `value`. With the `tracing` feature enabled this emits a
+    /// `tracing::info!` event instead of printing to stdout, so taint-safe
+    /// logging integrates with the rest of an app's tracing subscribers.
+    #[cfg(not(feature = "tracing"))]
+    pub fn log<T: Display>(&mut self, value: &Tainted<T, Public>) {
+        println!("[log] {}", value);
+    }
+
+    #[cfg(feature = "tracing")]
+    pub fn log<T: Display>(&mut self, value: &Tainted<T, Public>) {
+        emit_at(tracing::Level::INFO, value);
+    }
+
+    /// Like [`Logger::log`], but emits at an explicit tracing `level`
+    /// instead of the default `INFO`. Only available with the `tracing`
+    /// feature enabled.
+    #[cfg(feature = "tracing")]
+    pub fn log_at<T: Display>(&mut self, level: tracing::Level, value: &Tainted<T, Public>) {
+        emit_at(level, value);
+    }
+
+    /// Like [`Logger::log`], but first consults `policy`, skipping the log
+    /// and returning `false` if `policy` rejects `value`.
+    pub fn log_checked<T: Display, P: SinkPolicy>(&mut self, value: &Tainted<T, Public>, policy: &P) -> bool {
+        if !policy.allow(value) {
+            return false;
+        }
+        self.log(value);
+        true
+    }
+}
+
+impl TaintedSink for Logger {
+    fn accept<T: Display>(&mut self, value: &Tainted<T, Public>) {
+        self.log(value);
+    }
+}
+
+/// A sink that forwards `Public` data to the `tracing` ecosystem instead of
+/// stdout or a network egress point, at a configurable level. Requires the
+/// `tracing` feature.
+#[cfg(feature = "tracing")]
+pub struct TracingSink {
+    pub level: tracing::Level,
+}
+
+#[cfg(feature = "tracing")]
+impl TracingSink {
+    pub fn new(level: tracing::Level) -> Self {
+        TracingSink { level }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Default for TracingSink {
+    fn default() -> Self {
+        TracingSink::new(tracing::Level::INFO)
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl TaintedSink for TracingSink {
+    fn accept<T: Display>(&mut self, value: &Tainted<T, Public>) {
+        emit_at(self.level, value);
+    }
+}
+
+/// A stand-in for a network egress point; also `Public`-only.
+pub struct NetworkSink {
+    pub sent: Vec<String>,
+}
+
+impl NetworkSink {
+    pub fn new() -> Self {
+        NetworkSink { sent: Vec::new() }
+    }
+
+    pub fn send<T: Display>(&mut self, value: &Tainted<T, Public>) {
+        self.sent.push(value.to_string());
+    }
+}
+
+impl Default for NetworkSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaintedSink for NetworkSink {
+    fn accept<T: Display>(&mut self, value: &Tainted<T, Public>) {
+        self.send(value);
+    }
+}
+
+/// Wraps an inner [`TaintedSink`], redacting `Secret` values with a
+/// sanitizer function before they ever reach it.
+pub struct SanitizingSink<S: TaintedSink> {
+    inner: S,
+}
+
+impl<S: TaintedSink> SanitizingSink<S> {
+    pub fn new(inner: S) -> Self {
+        SanitizingSink { inner }
+    }
+
+    /// Sanitize a secret value with `sanitize` and forward the resulting
+    /// public value to the inner sink.
+    pub fn accept_secret<T, U: Display>(
+        &mut self,
+        value: &Tainted<T, Secret>,
+        sanitize: impl FnOnce(&T) -> U,
+    ) {
+        let redacted = Tainted::public(sanitize(value.expose_secret()));
+        self.inner.accept(&redacted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizing_sink_redacts_before_forwarding() {
+        let mut sink = SanitizingSink::new(NetworkSink::new());
+        let secret = Tainted::secret("hunter2".to_string());
+        sink.accept_secret(&secret, |_| "[REDACTED]".to_string());
+        assert_eq!(sink.inner.sent, vec!["[REDACTED]".to_string()]);
+    }
+
+    #[test]
+    fn digit_sequence_policy_blocks_a_card_like_public_value() {
+        let mut logger = Logger;
+        let policy = RejectDigitSequences { min_digits: 8 };
+
+        let card = Tainted::public("4111111111111111".to_string());
+        assert!(!logger.log_checked(&card, &policy));
+
+        let safe = Tainted::public("hello".to_string());
+        assert!(logger.log_checked(&safe, &policy));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tracing_test::traced_test]
+    #[test]
+    fn logger_emits_a_public_value_as_a_tracing_event() {
+        let mut logger = Logger;
+        let greeting = Tainted::public("hello, observability".to_string());
+        logger.log(&greeting);
+
+        assert!(tracing_test::internal::logs_with_scope_contain("taint", "hello, observability"));
+    }
+}
@@ -0,0 +1,317 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Add;
+
+use crate::level::{Confidential, Public, Secret, TaintLevel};
+
+/// A value of type `T` tagged at compile time with a taint level `L`.
+///
+/// `Tainted<T, Secret>` cannot be printed, logged, or serialized without an
+/// explicit [`Tainted::declassify`] call, which is the single chokepoint for
+/// turning secret data into public data.
+pub struct Tainted<T, L: TaintLevel> {
+    value: T,
+    _level: PhantomData<L>,
+}
+
+impl<T> Tainted<T, Secret> {
+    /// Wrap a value as secret.
+    pub fn secret(value: T) -> Self {
+        Tainted { value, _level: PhantomData }
+    }
+
+    /// Explicitly downgrade a secret value to public. This is the only way
+    /// to get at the raw value of a secret-tainted `Tainted`.
+    pub fn declassify(self) -> Tainted<T, Public> {
+        Tainted { value: self.value, _level: PhantomData }
+    }
+
+    /// Access the secret value. Named loudly so call sites are greppable.
+    pub fn expose_secret(&self) -> &T {
+        &self.value
+    }
+
+    /// Apply a redaction function `f` to the secret value and wrap its
+    /// output as `Public`, without the raw secret ever escaping `f`'s
+    /// scope. Generalizes the [`crate::sanitizer::Sanitizer`] pattern to
+    /// any redaction, not just the built-in hash/mask ones.
+    pub fn redact<U>(self, f: impl FnOnce(&T) -> U) -> Tainted<U, Public> {
+        Tainted::public(f(&self.value))
+    }
+
+    /// Like [`Tainted::declassify`], but first consults `guard`, erroring
+    /// with [`crate::audit::DeclassBudgetExceeded`] instead of
+    /// declassifying once `guard`'s budget is exhausted.
+    pub fn declassify_guarded(
+        self,
+        guard: &mut crate::audit::DeclassGuard,
+    ) -> Result<Tainted<T, Public>, crate::audit::DeclassBudgetExceeded> {
+        guard.consume()?;
+        Ok(self.declassify())
+    }
+
+    /// Downgrade to public, recording the reason and the declassified
+    /// type in `logger` so every declassification leaves an audit trail.
+    pub fn declassify_audited(
+        self,
+        reason: &str,
+        logger: &mut dyn crate::audit::DeclassifyAudit,
+    ) -> Tainted<T, Public> {
+        logger.record(crate::audit::AuditEntry {
+            sequence: 0,
+            reason: reason.to_string(),
+            type_name: std::any::type_name::<T>(),
+        });
+        self.declassify()
+    }
+}
+
+impl<T> Tainted<T, Public> {
+    /// Wrap a value as public.
+    pub fn public(value: T) -> Self {
+        Tainted { value, _level: PhantomData }
+    }
+
+    /// Access the public value.
+    pub fn expose(&self) -> &T {
+        &self.value
+    }
+
+    /// Consume and return the inner public value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Tainted<T, Confidential> {
+    /// Wrap a value as confidential (internal-only, but not top-secret).
+    pub fn confidential(value: T) -> Self {
+        Tainted { value, _level: PhantomData }
+    }
+
+    /// Access the confidential value.
+    pub fn expose_confidential(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, L: TaintLevel> Tainted<T, L> {
+    /// Transform the inner value while preserving the taint level.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Tainted<U, L> {
+        Tainted { value: f(self.value), _level: PhantomData }
+    }
+
+    /// Combine two tainted values, resolving to the more restrictive of the
+    /// two taint levels (`Public < Confidential < Secret`).
+    pub fn combine_with<U, L2: TaintLevel, R>(
+        self,
+        other: Tainted<U, L2>,
+        f: impl FnOnce(T, U) -> R,
+    ) -> Tainted<R, L::Combined<L2>> {
+        Tainted { value: f(self.value, other.value), _level: PhantomData }
+    }
+
+    /// Flatten a taint-preserving pipeline: `f` itself returns a `Tainted`
+    /// at the same level `L`, so the result isn't `Tainted<Tainted<U, L>, L>`.
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> Tainted<U, L>) -> Tainted<U, L> {
+        f(self.value)
+    }
+
+    /// Pair up two tainted values of the same level into one tuple, still
+    /// tainted at `L`.
+    pub fn zip<U>(self, other: Tainted<U, L>) -> Tainted<(T, U), L> {
+        Tainted { value: (self.value, other.value), _level: PhantomData }
+    }
+
+    /// Transform the inner value, discarding it (and the taint) if `f`
+    /// returns `None`.
+    pub fn filter_map<U>(self, f: impl FnOnce(T) -> Option<U>) -> Option<Tainted<U, L>> {
+        f(self.value).map(|value| Tainted { value, _level: PhantomData })
+    }
+
+    /// Fold a collection of same-level tainted values into one, preserving
+    /// the taint level `L`. Useful for summing secret shares or
+    /// concatenating secret fragments without a chain of pairwise
+    /// [`Tainted::combine_with`] calls.
+    pub fn combine_many<I, R>(items: I, init: R, mut f: impl FnMut(R, T) -> R) -> Tainted<R, L>
+    where
+        I: IntoIterator<Item = Tainted<T, L>>,
+    {
+        let value = items.into_iter().fold(init, |acc, item| f(acc, item.value));
+        Tainted { value, _level: PhantomData }
+    }
+}
+
+impl<T, L: TaintLevel> Clone for Tainted<T, L>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Tainted { value: self.value.clone(), _level: PhantomData }
+    }
+}
+
+impl<T, L: TaintLevel> fmt::Debug for Tainted<T, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if L::NAME == Secret::NAME {
+            write!(f, "Tainted<{}>([REDACTED])", L::NAME)
+        } else {
+            write!(f, "Tainted<{}>", L::NAME)
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Tainted<T, Public> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<T: Add<Output = T>, L: TaintLevel, L2: TaintLevel> Add<Tainted<T, L2>> for Tainted<T, L> {
+    type Output = Tainted<T, L::Combined<L2>>;
+
+    fn add(self, rhs: Tainted<T, L2>) -> Self::Output {
+        Tainted { value: self.value + rhs.value, _level: PhantomData }
+    }
+}
+
+/// Numeric types with an overflow-checked addition, so
+/// [`Tainted::checked_add`] doesn't have to commit to one integer width.
+/// Implemented for the built-in integer types; `Add` (used by `Tainted`'s
+/// `+` operator) panics on overflow in debug builds, which is a
+/// denial-of-service risk when the operands are attacker-influenced secret
+/// values.
+pub trait CheckedAdd: Sized {
+    fn checked_add_impl(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_add {
+    ($($t:ty),*) => {
+        $(
+            impl CheckedAdd for $t {
+                fn checked_add_impl(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_add!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl<T: CheckedAdd, L: TaintLevel> Tainted<T, L> {
+    /// Add two tainted numeric values, resolving to the more restrictive
+    /// taint level, without panicking on overflow: returns `None` (still
+    /// tainted at the combined level) instead.
+    pub fn checked_add<L2: TaintLevel>(self, rhs: Tainted<T, L2>) -> Tainted<Option<T>, L::Combined<L2>> {
+        Tainted { value: self.value.checked_add_impl(rhs.value), _level: PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::Confidential;
+
+    #[test]
+    fn confidential_combined_with_public_is_confidential() {
+        let confidential = Tainted::<i32, Confidential>::confidential(1);
+        let public = Tainted::<i32, Public>::public(2);
+        let combined: Tainted<i32, Confidential> = confidential.combine_with(public, |x, y| x + y);
+        assert_eq!(*combined.expose_confidential(), 3);
+    }
+
+    #[test]
+    fn and_then_flattens_and_keeps_secret_marker() {
+        let secret: Tainted<String, Secret> = Tainted::secret("hunter2".to_string());
+        let len: Tainted<usize, Secret> = secret.and_then(|s| Tainted::secret(s.len()));
+        assert_eq!(*len.expose_secret(), 7);
+    }
+
+    #[test]
+    fn zip_and_filter_map_preserve_level() {
+        let a: Tainted<i32, Secret> = Tainted::secret(1);
+        let b: Tainted<i32, Secret> = Tainted::secret(2);
+        let zipped = a.zip(b);
+        assert_eq!(*zipped.expose_secret(), (1, 2));
+
+        let kept: Option<Tainted<i32, Secret>> = Tainted::secret(4).filter_map(|x| (x % 2 == 0).then_some(x));
+        assert_eq!(*kept.unwrap().expose_secret(), 4);
+
+        let dropped: Option<Tainted<i32, Secret>> = Tainted::secret(5).filter_map(|x| (x % 2 == 0).then_some(x));
+        assert!(dropped.is_none());
+    }
+
+    #[test]
+    fn declassify_audited_records_each_call() {
+        use crate::audit::InMemoryAudit;
+
+        let mut audit = InMemoryAudit::new();
+        let a = Tainted::secret(1).declassify_audited("support ticket #1", &mut audit);
+        let b = Tainted::secret(2).declassify_audited("debugging", &mut audit);
+
+        assert_eq!(*a.expose(), 1);
+        assert_eq!(*b.expose(), 2);
+        let entries = audit.entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reason, "support ticket #1");
+        assert_eq!(entries[1].reason, "debugging");
+    }
+
+    #[test]
+    fn exceeding_the_declassification_budget_is_detected() {
+        use crate::audit::{DeclassBudgetExceeded, DeclassGuard};
+
+        let mut guard = DeclassGuard::new(2);
+        let a = Tainted::secret(1).declassify_guarded(&mut guard);
+        let b = Tainted::secret(2).declassify_guarded(&mut guard);
+        let c = Tainted::secret(3).declassify_guarded(&mut guard);
+
+        assert_eq!(*a.unwrap().expose(), 1);
+        assert_eq!(*b.unwrap().expose(), 2);
+        assert_eq!(c.unwrap_err(), DeclassBudgetExceeded { limit: 2 });
+        assert_eq!(guard.count(), 2);
+    }
+
+    #[test]
+    fn redact_summarizes_a_secret_struct_without_exposing_it() {
+        struct CreditCard {
+            number: String,
+            holder: String,
+        }
+
+        let card = Tainted::secret(CreditCard { number: "4111111111111111".to_string(), holder: "A. Cardholder".to_string() });
+        let summary = card.redact(|c| format!("{}'s card ending in {}", c.holder, &c.number[c.number.len() - 4..]));
+
+        assert_eq!(summary.expose(), "A. Cardholder's card ending in 1111");
+    }
+
+    #[test]
+    fn combine_many_folds_secret_values_preserving_the_level() {
+        let shares: Vec<Tainted<i32, Secret>> = vec![Tainted::secret(1), Tainted::secret(2), Tainted::secret(3)];
+        let sum: Tainted<i32, Secret> = Tainted::combine_many(shares, 0, |acc, x| acc + x);
+        assert_eq!(*sum.declassify().expose(), 6);
+    }
+
+    #[test]
+    fn checked_add_of_two_near_max_secrets_returns_a_taint_preserving_none_instead_of_panicking() {
+        let a: Tainted<u32, Secret> = Tainted::secret(u32::MAX - 1);
+        let b: Tainted<u32, Secret> = Tainted::secret(2);
+
+        let overflowed: Tainted<Option<u32>, Secret> = a.checked_add(b);
+        assert!(overflowed.expose_secret().is_none());
+
+        let fits: Tainted<u32, Secret> = Tainted::secret(1);
+        let other: Tainted<u32, Secret> = Tainted::secret(2);
+        let sum: Tainted<Option<u32>, Secret> = fits.checked_add(other);
+        assert_eq!(*sum.expose_secret(), Some(3));
+    }
+
+    #[test]
+    fn confidential_combined_with_secret_is_secret() {
+        let confidential = Tainted::<i32, Confidential>::confidential(1);
+        let secret = Tainted::<i32, Secret>::secret(2);
+        let combined: Tainted<i32, Secret> = confidential.combine_with(secret, |x, y| x + y);
+        assert_eq!(*combined.expose_secret(), 3);
+    }
+}
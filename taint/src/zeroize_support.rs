@@ -0,0 +1,87 @@
+//! An opt-in secret wrapper that wipes its contents from memory on drop.
+
+use std::mem::ManuallyDrop;
+
+use zeroize::Zeroize;
+
+use crate::level::{Public, Secret};
+use crate::tainted::Tainted;
+
+/// Like `Tainted<T, Secret>`, but its memory is zeroized when dropped.
+///
+/// `declassify`/`into_secret` move the value out via [`ManuallyDrop::take`]
+/// and `mem::forget` the wrapper, so the value is never wiped twice.
+pub struct SecretZ<T: Zeroize> {
+    value: ManuallyDrop<T>,
+}
+
+impl<T: Zeroize> SecretZ<T> {
+    pub fn new(value: T) -> Self {
+        SecretZ { value: ManuallyDrop::new(value) }
+    }
+
+    pub fn expose_secret(&self) -> &T {
+        &self.value
+    }
+
+    /// Downgrade to public, moving the value out without zeroizing it.
+    pub fn declassify(mut self) -> Tainted<T, Public> {
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        std::mem::forget(self);
+        Tainted::public(value)
+    }
+
+    /// Move the value into a plain (non-zeroizing) `Tainted<T, Secret>`.
+    pub fn into_secret(mut self) -> Tainted<T, Secret> {
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        std::mem::forget(self);
+        Tainted::secret(value)
+    }
+}
+
+impl<T: Zeroize> From<T> for SecretZ<T> {
+    fn from(value: T) -> Self {
+        SecretZ::new(value)
+    }
+}
+
+impl<T: Zeroize> Drop for SecretZ<T> {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct RecordingSecret {
+        wipes: Arc<AtomicUsize>,
+    }
+
+    impl Zeroize for RecordingSecret {
+        fn zeroize(&mut self) {
+            self.wipes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn drop_triggers_zeroization() {
+        let wipes = Arc::new(AtomicUsize::new(0));
+        {
+            let _secret = SecretZ::new(RecordingSecret { wipes: wipes.clone() });
+        }
+        assert_eq!(wipes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn declassify_does_not_double_wipe() {
+        let wipes = Arc::new(AtomicUsize::new(0));
+        let secret = SecretZ::new(RecordingSecret { wipes: wipes.clone() });
+        let public = secret.declassify();
+        drop(public);
+        assert_eq!(wipes.load(Ordering::SeqCst), 0);
+    }
+}